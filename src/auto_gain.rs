@@ -0,0 +1,264 @@
+use embedded_hal_async::i2c::I2c;
+
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::{
+    error::Error,
+    mode::{AllowReadMeasurement, CurrentOperatingMode, OperatingMode, ReadyMode},
+    read_measurements::convert_raw_ambient_to_milli_lux,
+    register::{AmbientStatusErrorCode, AMBIENT_ANALOGUE_GAIN_MILLI},
+    units::{MilliLux, RawAmbientCount},
+    VL6180X,
+};
+
+#[cfg(test)]
+mod auto_gain_tests;
+
+/// Highest analogue gain level
+const MAX_GAIN_LEVEL: u8 = 7;
+
+/// Ambient light sample returned by [AmbientAutoRanger], with the settings it was
+/// measured with.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct AmbientSample {
+    /// Raw ambient light count, or the status error the measurement failed with.
+    pub raw: Result<RawAmbientCount, AmbientStatusErrorCode>,
+    /// Analogue gain level the sample was measured with, see
+    /// [`Config::set_ambient_analogue_gain_level()`](crate::Config::set_ambient_analogue_gain_level).
+    pub gain_level: u8,
+    /// Integration period in ms the sample was measured with.
+    pub integration_period_ms: u16,
+}
+
+impl AmbientSample {
+    /// Ambient light in lux, converted with the gain and integration period of the
+    /// sample. `None` if the measurement overflowed or underflowed.
+    #[cfg(feature = "float")]
+    pub fn lux(&self) -> Option<Lux> {
+        self.raw.ok().map(|raw| {
+            crate::read_measurements::convert_raw_ambient_to_lux(
+                raw,
+                self.gain_level,
+                self.integration_period_ms,
+            )
+        })
+    }
+
+    /// Ambient light in milli-lux, converted with integer maths only. `None` if the
+    /// measurement overflowed or underflowed.
+    pub fn milli_lux(&self) -> Option<MilliLux> {
+        self.raw.ok().map(|raw| {
+            convert_raw_ambient_to_milli_lux(raw, self.gain_level, self.integration_period_ms)
+        })
+    }
+}
+
+/// Adjusts the ambient light analogue gain and integration period at runtime, keeping
+/// the raw count in a window where it neither saturates in sunlight nor loses
+/// resolution indoors.
+///
+/// After each sample the settings for the following measurements are picked from the
+/// raw count, aiming for the middle of the count window. The longest integration
+/// period is preferred for flicker rejection, so the gain is lowered first and the
+/// integration period is only shortened at the lowest gain. An overflow lowers the
+/// sensitivity eight times, an underflow raises it eight times.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct AmbientAutoRanger {
+    low_count: u16,
+    high_count: u16,
+    min_integration_period_ms: u16,
+    max_integration_period_ms: u16,
+    discard_next: bool,
+}
+
+impl AmbientAutoRanger {
+    /// Create a new auto ranger with default values.
+    pub fn new() -> Self {
+        AmbientAutoRanger {
+            low_count: 1_000,
+            high_count: 40_000,
+            min_integration_period_ms: 10,
+            max_integration_period_ms: 100,
+            discard_next: false,
+        }
+    }
+
+    /// Set the window of raw counts that is kept without changing the settings.
+    ///
+    /// `low_count` must be less than `high_count`; Default = 1000 to 40000;
+    pub fn set_count_window(
+        &mut self,
+        low_count: u16,
+        high_count: u16,
+    ) -> Result<(), Error<()>> {
+        if low_count >= high_count {
+            return Err(Error::InvalidConfigurationValue(low_count));
+        }
+        self.low_count = low_count;
+        self.high_count = high_count;
+        Ok(())
+    }
+
+    /// Set the shortest and longest integration period in ms to use.
+    ///
+    /// Min = 1ms; Max = 256ms; Default = 10ms to 100ms;
+    ///
+    /// Periods longer than the
+    /// [ambient_inter_measurement_period](crate::Config::set_ambient_inter_measurement_period)
+    /// allows are not used, see
+    /// [`VL6180X::set_ambient_integration_period()`].
+    pub fn set_integration_limits(
+        &mut self,
+        min_ms: u16,
+        max_ms: u16,
+    ) -> Result<(), Error<()>> {
+        if min_ms < 1 || min_ms > max_ms {
+            return Err(Error::InvalidConfigurationValue(min_ms));
+        }
+        if max_ms > 256 {
+            return Err(Error::InvalidConfigurationValue(max_ms));
+        }
+        self.min_integration_period_ms = min_ms;
+        self.max_integration_period_ms = max_ms;
+        Ok(())
+    }
+
+    /// The gain level and integration period to use after `sample`, with an integration
+    /// period of at most `longest_period_ms`, the longest the inter-measurement period
+    /// allows.
+    pub fn next_settings(&self, sample: &AmbientSample, longest_period_ms: u16) -> (u8, u16) {
+        let current = (sample.gain_level, sample.integration_period_ms);
+        let sensitivity = AMBIENT_ANALOGUE_GAIN_MILLI[sample.gain_level as usize] as u64 *
+            sample.integration_period_ms as u64;
+        let target = match sample.raw {
+            Err(AmbientStatusErrorCode::Overflow) => sensitivity / 8,
+            Err(AmbientStatusErrorCode::Underflow) => sensitivity * 8,
+            Err(AmbientStatusErrorCode::NoError) => return current,
+            Ok(RawAmbientCount(raw)) if raw > self.high_count || raw < self.low_count => {
+                let target_count = (self.low_count as u64 + self.high_count as u64) / 2;
+                sensitivity * target_count / (raw as u64).max(1)
+            }
+            Ok(_) => return current,
+        };
+
+        let max_ms = self.max_integration_period_ms.min(longest_period_ms).max(1);
+        let min_ms = self.min_integration_period_ms.min(max_ms);
+        let gain_level = (0..=MAX_GAIN_LEVEL)
+            .rev()
+            .find(|level| {
+                AMBIENT_ANALOGUE_GAIN_MILLI[*level as usize] as u64 * max_ms as u64 <= target
+            })
+            .unwrap_or(0);
+        let integration_period_ms = (target /
+            AMBIENT_ANALOGUE_GAIN_MILLI[gain_level as usize] as u64)
+            .clamp(min_ms as u64, max_ms as u64) as u16;
+        (gain_level, integration_period_ms)
+    }
+
+    /// Blocking read of the ambient light measurement, see
+    /// [`VL6180X::read_ambient_blocking()`], changing the settings for the following
+    /// measurements if needed.
+    ///
+    /// In continuous modes the measurement after a change may have started with the old
+    /// settings, so that result is discarded and the next one is waited for.
+    pub async fn read_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<AmbientSample, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        if self.discard_next {
+            self.discard_next = false;
+            let _ = Self::sample(vl6180x.read_ambient_blocking().await)?;
+        }
+        let raw = Self::sample(vl6180x.read_ambient_blocking().await)?;
+        self.step(vl6180x, raw).await
+    }
+
+    /// Non-blocking read of the ambient light measurement, see
+    /// [`VL6180X::read_ambient()`], changing the settings for the following
+    /// measurements if needed.
+    ///
+    /// In continuous modes the measurement after a change may have started with the old
+    /// settings, so that result is discarded and [Error::ResultNotReady] is returned
+    /// instead.
+    pub async fn read<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<AmbientSample, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        let raw = Self::sample(vl6180x.read_ambient().await)?;
+        if self.discard_next {
+            self.discard_next = false;
+            return Err(Error::ResultNotReady);
+        }
+        self.step(vl6180x, raw).await
+    }
+
+    /// Single ambient light measurement, changing the settings for the following
+    /// measurements if needed.
+    pub async fn poll_single_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<ReadyMode, I2C>,
+    ) -> Result<AmbientSample, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        vl6180x.start_ambient_single().await?;
+        let raw = Self::sample(vl6180x.read_ambient_blocking().await)?;
+        self.step(vl6180x, raw).await
+    }
+
+    /// Keeps overflow and underflow as part of the sample.
+    fn sample<E>(
+        result: Result<RawAmbientCount, Error<E>>,
+    ) -> Result<Result<RawAmbientCount, AmbientStatusErrorCode>, Error<E>> {
+        match result {
+            Ok(raw) => Ok(Ok(raw)),
+            Err(Error::AmbientStatusError(code)) => Ok(Err(code)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Changes the settings if needed.
+    async fn step<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+        raw: Result<RawAmbientCount, AmbientStatusErrorCode>,
+    ) -> Result<AmbientSample, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: CurrentOperatingMode,
+    {
+        let sample = AmbientSample {
+            raw,
+            gain_level: vl6180x.config.ambient_analogue_gain_level,
+            integration_period_ms: vl6180x.config.ambient_integration_period,
+        };
+        let (gain_level, integration_period_ms) =
+            self.next_settings(&sample, vl6180x.max_ambient_integration_period());
+        if (gain_level, integration_period_ms) !=
+            (sample.gain_level, sample.integration_period_ms)
+        {
+            vl6180x
+                .set_ambient_gain_and_integration_direct(gain_level, integration_period_ms)
+                .await?;
+            // Single measurements start after the change
+            self.discard_next = vl6180x.mode.current_operating_mode() != OperatingMode::Ready;
+        }
+        Ok(sample)
+    }
+}
+
+impl Default for AmbientAutoRanger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,329 @@
+use embassy_futures::block_on;
+use embedded_hal::i2c::ErrorKind;
+
+use super::*;
+use crate::{
+    mock,
+    mode::{AmbientContinuousMode, InterleavedContinuousMode},
+    units::Milliseconds,
+};
+
+fn sample(
+    raw: Result<u16, AmbientStatusErrorCode>,
+    gain_level: u8,
+    period: u16,
+) -> AmbientSample {
+    AmbientSample {
+        raw: raw.map(RawAmbientCount),
+        gain_level,
+        integration_period_ms: period,
+    }
+}
+
+/// Longest integration period, so the inter-measurement period does not limit it
+const UNLIMITED: u16 = 256;
+
+#[test]
+fn keeps_settings_inside_count_window() {
+    let ranger = AmbientAutoRanger::new();
+    assert_eq!(
+        ranger.next_settings(&sample(Ok(1_000), 3, 100), UNLIMITED),
+        (3, 100)
+    );
+    assert_eq!(
+        ranger.next_settings(&sample(Ok(40_000), 3, 100), UNLIMITED),
+        (3, 100)
+    );
+}
+
+#[test]
+fn overflow_lowers_gain_then_integration_period() {
+    let ranger = AmbientAutoRanger::new();
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Overflow), 7, 100),
+            UNLIMITED
+        ),
+        (3, 100)
+    );
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Overflow), 0, 100),
+            UNLIMITED
+        ),
+        (0, 12)
+    );
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Overflow), 0, 12),
+            UNLIMITED
+        ),
+        (0, 10)
+    );
+}
+
+#[test]
+fn underflow_raises_integration_period_then_gain() {
+    let ranger = AmbientAutoRanger::new();
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Underflow), 0, 10),
+            UNLIMITED
+        ),
+        (0, 80)
+    );
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Underflow), 0, 100),
+            UNLIMITED
+        ),
+        (4, 100)
+    );
+}
+
+#[test]
+fn aims_for_middle_of_count_window() {
+    let ranger = AmbientAutoRanger::new();
+    assert_eq!(
+        ranger.next_settings(&sample(Ok(100), 0, 100), UNLIMITED),
+        (7, 100)
+    );
+    assert_eq!(
+        ranger.next_settings(&sample(Ok(60_000), 7, 100), UNLIMITED),
+        (5, 100)
+    );
+    assert_eq!(
+        ranger.next_settings(&sample(Ok(0), 7, 100), UNLIMITED),
+        (7, 100)
+    );
+}
+
+#[test]
+fn limits_validation() {
+    let mut ranger = AmbientAutoRanger::new();
+    assert_eq!(
+        ranger.set_count_window(10, 10),
+        Err(Error::InvalidConfigurationValue(10))
+    );
+    assert_eq!(
+        ranger.set_integration_limits(0, 100),
+        Err(Error::InvalidConfigurationValue(0))
+    );
+    assert_eq!(
+        ranger.set_integration_limits(10, 257),
+        Err(Error::InvalidConfigurationValue(257))
+    );
+    ranger.set_integration_limits(50, 50).unwrap();
+    assert_eq!(
+        ranger.next_settings(
+            &sample(Err(AmbientStatusErrorCode::Overflow), 0, 50),
+            UNLIMITED
+        ),
+        (0, 50)
+    );
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn lux_uses_settings_of_sample() {
+    let bright = sample(Ok(1_000), 0, 100);
+    let dark = sample(Ok(1_000), 6, 50);
+    assert_eq!(bright.lux(), Some(Lux(32.0 / 1.01 * 10.0)));
+    assert_eq!(dark.lux(), Some(Lux(32.0 / 20.0 * 20.0)));
+    assert_eq!(
+        sample(Err(AmbientStatusErrorCode::Overflow), 0, 100).lux(),
+        None
+    );
+}
+
+#[test]
+fn milli_lux_uses_settings_of_sample() {
+    assert_eq!(
+        sample(Ok(1_000), 0, 100).milli_lux(),
+        Some(MilliLux(316_832))
+    );
+    assert_eq!(sample(Ok(1_000), 6, 50).milli_lux(), Some(MilliLux(32_000)));
+    assert_eq!(
+        sample(Err(AmbientStatusErrorCode::Overflow), 0, 100).milli_lux(),
+        None
+    );
+}
+
+#[test]
+fn runtime_gain_and_integration_change() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    block_on(sensor.set_ambient_analogue_gain_level(7)).unwrap();
+    block_on(sensor.set_ambient_integration_period(Milliseconds(50))).unwrap();
+    assert_eq!(
+        sensor.com.writes,
+        [
+            (0x017, 1),
+            (0x03F, 0x47),
+            (0x040, 0),
+            (0x041, 99),
+            (0x017, 0),
+            (0x017, 1),
+            (0x03F, 0x47),
+            (0x040, 0),
+            (0x041, 49),
+            (0x017, 0)
+        ]
+    );
+    assert_eq!(sensor.config.ambient_analogue_gain_level, 7);
+    assert_eq!(sensor.config.ambient_integration_period, 50);
+
+    assert_eq!(
+        block_on(sensor.set_ambient_analogue_gain_level(8)),
+        Err(Error::InvalidConfigurationValue(8))
+    );
+    sensor.config.ambient_inter_measurement_period = 100;
+    assert_eq!(
+        block_on(sensor.set_ambient_integration_period(Milliseconds(82))),
+        Err(Error::InvalidConfigurationValue(82))
+    );
+    assert_eq!(
+        block_on(sensor.set_ambient_integration_period(Milliseconds(81))),
+        Ok(())
+    );
+}
+
+fn set_ambient(sensor: &mut VL6180X<impl Sized, mock::MockI2c>, status: u8, raw: u16) {
+    sensor.com.set(0x04F, 0b00_100_000);
+    sensor.com.set(0x04E, status << 4 | 0x01);
+    sensor.com.set(0x050, (raw >> 8) as u8);
+    sensor.com.set(0x051, raw as u8);
+}
+
+#[test]
+fn poll_single_reports_settings_of_sample() {
+    let mut sensor = mock::sensor(ReadyMode);
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0b0001, 0);
+    assert_eq!(
+        block_on(ranger.poll_single_blocking(&mut sensor)),
+        Ok(sample(Err(AmbientStatusErrorCode::Overflow), 0, 100))
+    );
+    assert_eq!(sensor.config.ambient_integration_period, 12);
+    assert!(sensor.com.was_written(0x038, 0b01));
+
+    set_ambient(&mut sensor, 0, 20_000);
+    assert_eq!(
+        block_on(ranger.poll_single_blocking(&mut sensor)),
+        Ok(sample(Ok(20_000), 0, 12))
+    );
+}
+
+#[test]
+fn continuous_read_discards_sample_after_change() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0, 50_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Ok(sample(Ok(50_000), 0, 100))
+    );
+    set_ambient(&mut sensor, 0, 10_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Err(Error::ResultNotReady)
+    );
+    set_ambient(&mut sensor, 0, 20_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Ok(sample(Ok(20_000), 0, 41))
+    );
+}
+
+#[test]
+fn interleaved_integration_period_leaves_time_for_ranging() {
+    let mut sensor = mock::sensor(InterleavedContinuousMode {});
+    sensor.config.ambient_inter_measurement_period = 100;
+    // (49 + 5) + 32 * 1.1 ≤ 100 * 0.9
+    assert_eq!(
+        block_on(sensor.set_ambient_integration_period(Milliseconds(33))),
+        Err(Error::InvalidConfigurationValue(33))
+    );
+    assert_eq!(
+        block_on(sensor.set_ambient_integration_period(Milliseconds(32))),
+        Ok(())
+    );
+}
+
+#[test]
+fn integration_period_is_limited_by_inter_measurement_period() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    sensor.config.ambient_inter_measurement_period = 100;
+    sensor.config.ambient_integration_period = 50;
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0b0010, 0);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Ok(sample(Err(AmbientStatusErrorCode::Underflow), 0, 50))
+    );
+    assert_eq!(sensor.config.ambient_analogue_gain_level, 3);
+    assert_eq!(sensor.config.ambient_integration_period, 81);
+}
+
+#[test]
+fn failed_change_is_returned() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    // Too short for any integration period
+    sensor.config.ambient_inter_measurement_period = 1;
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0, 60_000);
+    assert!(matches!(
+        block_on(ranger.read(&mut sensor)),
+        Err(Error::InvalidConfigurationValue(_))
+    ));
+    assert_eq!(sensor.config.ambient_integration_period, 100);
+    // The next sample tries again
+    set_ambient(&mut sensor, 0, 60_000);
+    assert!(block_on(ranger.read(&mut sensor)).is_err());
+}
+
+#[test]
+fn failed_change_releases_parameter_hold() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    let mut ranger = AmbientAutoRanger::new();
+    sensor.com.failing_register = Some(0x03F);
+    set_ambient(&mut sensor, 0, 60_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Err(Error::BusError(ErrorKind::Bus))
+    );
+    assert_eq!(sensor.com.writes.last(), Some(&(0x017, 0)));
+    assert_eq!(sensor.config.ambient_analogue_gain_level, 0);
+    // The read after the failed change is not discarded
+    set_ambient(&mut sensor, 0, 20_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Ok(sample(Ok(20_000), 0, 100))
+    );
+}
+
+#[test]
+fn single_shot_read_is_not_discarded_after_change() {
+    let mut sensor = mock::sensor(ReadyMode);
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0, 50_000);
+    block_on(ranger.read(&mut sensor)).unwrap();
+    set_ambient(&mut sensor, 0, 20_000);
+    assert_eq!(
+        block_on(ranger.read(&mut sensor)),
+        Ok(sample(Ok(20_000), 0, 41))
+    );
+}
+
+#[test]
+fn failed_discard_read_is_not_repeated() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    let mut ranger = AmbientAutoRanger::new();
+    set_ambient(&mut sensor, 0, 50_000);
+    block_on(ranger.read_blocking(&mut sensor)).unwrap();
+    sensor.com.bus_failures = 1;
+    assert!(matches!(
+        block_on(ranger.read_blocking(&mut sensor)),
+        Err(Error::BusError(_))
+    ));
+    assert!(!ranger.discard_next);
+}
@@ -0,0 +1,185 @@
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    error::Error,
+    mode::{AllowReadMeasurement, ReadyMode},
+    units::Millimeters,
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod auto_scale_tests;
+
+/// Largest raw range value, the maximum range is this times the scaler.
+const MAX_RAW_RANGE: u16 = 255;
+
+/// Range sample returned by [RangeAutoScaler].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct ScaledRange {
+    /// The measured range.
+    pub range: Range,
+    /// The range scaler the sample was measured with, 1x to 3x.
+    pub scaler: u8,
+}
+
+/// Selects the range scaler at runtime, for the resolution of 1x on near targets and the
+/// range of 3x on far ones.
+///
+/// Steps the scaler up when a result is [Range::OutOfRange], meaning
+/// [RangingAlgoOverflow](crate::RangeStatusErrorCode::RangingAlgoOverflow) or
+/// [RawRangingAlgoOverflow](crate::RangeStatusErrorCode::RawRangingAlgoOverflow),
+/// and down when the target comes within the range of the lower scaler.
+/// The scaler is changed with [`VL6180X::set_range_result_scaler()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RangeAutoScaler {
+    min_scaler: u8,
+    max_scaler: u8,
+    step_down_percent: u8,
+    discard_next: bool,
+}
+
+impl RangeAutoScaler {
+    /// Create a new auto scaler using all scalers from 1x to 3x.
+    pub fn new() -> Self {
+        RangeAutoScaler {
+            min_scaler: 1,
+            max_scaler: 3,
+            step_down_percent: 80,
+            discard_next: false,
+        }
+    }
+
+    /// Set the lowest and highest scaler to use.
+    ///
+    /// Min = 1x; Max = 3x; Default = 1x to 3x;
+    pub fn set_scaler_limits(
+        &mut self,
+        min_scaler: u8,
+        max_scaler: u8,
+    ) -> Result<(), Error<()>> {
+        if min_scaler < 1 || min_scaler > max_scaler {
+            return Err(Error::InvalidConfigurationValue(min_scaler as u16));
+        }
+        if max_scaler > 3 {
+            return Err(Error::InvalidConfigurationValue(max_scaler as u16));
+        }
+        self.min_scaler = min_scaler;
+        self.max_scaler = max_scaler;
+        Ok(())
+    }
+
+    /// Set how far into the range of the lower scaler, in percent of its maximum range,
+    /// a target must come to step down. Lower values leave more hysteresis between
+    /// stepping up and down.
+    ///
+    /// Min = 1%; Max = 100%; Default = 80%;
+    pub fn set_step_down_percent(&mut self, percent: u8) -> Result<(), Error<()>> {
+        if !(1..=100).contains(&percent) {
+            return Err(Error::InvalidConfigurationValue(percent as u16));
+        }
+        self.step_down_percent = percent;
+        Ok(())
+    }
+
+    /// The scaler to use after a sample measured with `scaler`.
+    pub fn next_scaler(&self, scaler: u8, range: Range) -> u8 {
+        let scaler = scaler.max(self.min_scaler).min(self.max_scaler);
+        match range {
+            Range::OutOfRange if scaler < self.max_scaler => scaler + 1,
+            Range::Mm(Millimeters(mm)) if scaler > self.min_scaler => {
+                let lower_max_mm = MAX_RAW_RANGE * (scaler - 1) as u16;
+                if (mm as u32) * 100 < lower_max_mm as u32 * self.step_down_percent as u32 {
+                    scaler - 1
+                } else {
+                    scaler
+                }
+            }
+            _ => scaler,
+        }
+    }
+
+    /// Blocking read of the range measurement, see [`VL6180X::read_range_blocking()`],
+    /// changing the scaler for the following measurements if needed.
+    ///
+    /// In continuous modes the measurement after a scaler change may have started
+    /// with the old scaler, so that result is discarded and the next one is waited for.
+    pub async fn read_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<ScaledRange, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement,
+    {
+        if self.discard_next {
+            vl6180x.read_range_blocking().await?;
+            self.discard_next = false;
+        }
+        let range = vl6180x.read_range_blocking().await?;
+        self.step(vl6180x, range).await
+    }
+
+    /// Non-blocking read of the range measurement, see [`VL6180X::read_range()`],
+    /// changing the scaler for the following measurements if needed.
+    ///
+    /// In continuous modes the measurement after a scaler change may have started
+    /// with the old scaler, so that result is discarded and
+    /// [Error::ResultNotReady] is returned instead.
+    pub async fn read<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<ScaledRange, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement,
+    {
+        let range = vl6180x.read_range().await?;
+        if self.discard_next {
+            self.discard_next = false;
+            return Err(Error::ResultNotReady);
+        }
+        self.step(vl6180x, range).await
+    }
+
+    /// Single range measurement, see [`VL6180X::poll_range_single_blocking()`],
+    /// changing the scaler for the following measurements if needed.
+    pub async fn poll_single_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<ReadyMode, I2C>,
+    ) -> Result<ScaledRange, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        self.discard_next = false;
+        let range = vl6180x.poll_range_single_blocking().await?;
+        let sample = self.step(vl6180x, range).await?;
+        // Single measurements start after the change
+        self.discard_next = false;
+        Ok(sample)
+    }
+
+    async fn step<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+        range: Range,
+    ) -> Result<ScaledRange, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        let scaler = vl6180x.config.range_scaling;
+        let next_scaler = self.next_scaler(scaler, range);
+        if next_scaler != scaler {
+            vl6180x.set_range_result_scaler_direct(next_scaler).await?;
+            self.discard_next = true;
+        }
+        Ok(ScaledRange { range, scaler })
+    }
+}
+
+impl Default for RangeAutoScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
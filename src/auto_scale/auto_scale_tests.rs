@@ -0,0 +1,147 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::{config::RangeInterruptMode, mock, mode::RangeContinuousMode};
+
+#[test]
+fn steps_up_on_overflow() {
+    let scaler = RangeAutoScaler::new();
+    assert_eq!(scaler.next_scaler(1, Range::OutOfRange), 2);
+    assert_eq!(scaler.next_scaler(2, Range::OutOfRange), 3);
+    assert_eq!(scaler.next_scaler(3, Range::OutOfRange), 3);
+    assert_eq!(scaler.next_scaler(1, Range::NoTarget), 1);
+}
+
+#[test]
+fn steps_down_with_hysteresis() {
+    let scaler = RangeAutoScaler::new();
+    // 80% of the 510mm range of 2x
+    assert_eq!(scaler.next_scaler(3, Range::Mm(Millimeters(407))), 2);
+    assert_eq!(scaler.next_scaler(3, Range::Mm(Millimeters(408))), 3);
+    // 80% of the 255mm range of 1x
+    assert_eq!(scaler.next_scaler(2, Range::Mm(Millimeters(203))), 1);
+    assert_eq!(scaler.next_scaler(2, Range::Mm(Millimeters(204))), 2);
+    assert_eq!(scaler.next_scaler(1, Range::Mm(Millimeters(10))), 1);
+}
+
+#[test]
+fn scaler_limits() {
+    let mut scaler = RangeAutoScaler::new();
+    assert_eq!(
+        scaler.set_scaler_limits(2, 1),
+        Err(Error::InvalidConfigurationValue(2))
+    );
+    assert_eq!(
+        scaler.set_scaler_limits(1, 4),
+        Err(Error::InvalidConfigurationValue(4))
+    );
+    assert_eq!(
+        scaler.set_step_down_percent(101),
+        Err(Error::InvalidConfigurationValue(101))
+    );
+    scaler.set_scaler_limits(2, 2).unwrap();
+    assert_eq!(scaler.next_scaler(1, Range::Mm(Millimeters(10))), 2);
+    assert_eq!(scaler.next_scaler(2, Range::OutOfRange), 2);
+}
+
+#[test]
+fn set_range_result_scaler_reprograms_safely() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    sensor.config.ptp_offset = 30;
+    sensor.com.set(0x02D, 0b0001_0001);
+    block_on(sensor.set_range_result_scaler(2)).unwrap();
+    assert_eq!(
+        sensor.com.writes,
+        [
+            (0x017, 1),
+            (0x096, 0),
+            (0x097, 127),
+            (0x024, 15),
+            (0x021, 10),
+            (0x02D, 0b0001_0000),
+            (0x017, 0)
+        ]
+    );
+    assert_eq!(sensor.config.range_scaling, 2);
+    assert_eq!(
+        block_on(sensor.set_range_result_scaler(4)),
+        Err(Error::InvalidConfigurationValue(4))
+    );
+}
+
+#[test]
+fn set_range_result_scaler_keeps_threshold_distances() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    block_on(sensor.set_range_interrupt(RangeInterruptMode::OutOfWindow, 30, 100)).unwrap();
+    sensor.com.writes.clear();
+    block_on(sensor.set_range_result_scaler(3)).unwrap();
+    assert!(sensor.com.was_written(0x019, 33));
+    assert!(sensor.com.was_written(0x01A, 10));
+    assert_eq!(sensor.com.writes.last(), Some(&(0x017, 0)));
+    assert_eq!(sensor.config.range_low_interrupt_threshold, 10);
+    assert_eq!(sensor.config.range_high_interrupt_threshold, 33);
+
+    block_on(sensor.set_range_result_scaler(1)).unwrap();
+    assert_eq!(sensor.config.range_low_interrupt_threshold, 30);
+    assert_eq!(sensor.config.range_high_interrupt_threshold, 99);
+
+    // The end of the range is kept
+    block_on(sensor.set_range_interrupt(RangeInterruptMode::LevelLow, 0, 0xFF)).unwrap();
+    block_on(sensor.set_range_result_scaler(2)).unwrap();
+    assert_eq!(sensor.config.range_high_interrupt_threshold, 0xFF);
+}
+
+#[test]
+fn poll_single_reports_scaler_and_steps_up() {
+    let mut sensor = mock::sensor(ReadyMode);
+    let mut auto_scaler = RangeAutoScaler::new();
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, 0b1111_0001);
+    assert_eq!(
+        block_on(auto_scaler.poll_single_blocking(&mut sensor)),
+        Ok(ScaledRange {
+            range: Range::OutOfRange,
+            scaler: 1,
+        })
+    );
+    assert_eq!(sensor.config.range_scaling, 2);
+
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, 0b0000_0001);
+    sensor.com.set(0x062, 200);
+    assert_eq!(
+        block_on(auto_scaler.poll_single_blocking(&mut sensor)),
+        Ok(ScaledRange {
+            range: Range::Mm(Millimeters(400)),
+            scaler: 2,
+        })
+    );
+}
+
+#[test]
+fn continuous_read_discards_sample_after_step() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    let mut auto_scaler = RangeAutoScaler::new();
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, 0b1101_0001);
+    block_on(auto_scaler.read(&mut sensor)).unwrap();
+    assert_eq!(sensor.config.range_scaling, 2);
+
+    sensor.com.set(0x04F, 0b00_000_100);
+    assert_eq!(
+        block_on(auto_scaler.read(&mut sensor)),
+        Err(Error::ResultNotReady)
+    );
+
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, 0b0000_0001);
+    sensor.com.set(0x062, 150);
+    assert_eq!(
+        block_on(auto_scaler.read(&mut sensor)),
+        Ok(ScaledRange {
+            range: Range::Mm(Millimeters(300)),
+            scaler: 2,
+        })
+    );
+    assert_eq!(sensor.config.range_scaling, 2);
+}
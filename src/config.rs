@@ -3,6 +3,9 @@ use super::*;
 #[cfg(test)]
 mod config_tests;
 
+/// I2C address the device answers to after booting.
+pub(crate) const DEFAULT_I2C_ADDRESS: u8 = 0x29;
+
 /// Options for configuring the interrupt trigger condition for ambient measurement.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
@@ -72,7 +75,7 @@ impl Config {
     /// Defaults are based on values from [ST application note AN4545](https://www.st.com/resource/en/application_note/an4545-vl6180x-basic-ranging-application-note-stmicroelectronics.pdf)
     pub fn new() -> Self {
         Config {
-            address: 0x29,
+            address: DEFAULT_I2C_ADDRESS,
             ptp_offset: 0,
             poll_max_loop: 500,
 
@@ -116,11 +119,15 @@ impl Config {
     /// Reducing the max convergence time will reduce the maximum time a measurement will be
     /// allowed to complete and can reduce the power consumption when no target is present. We
     /// recommend a value of 30ms for the max convergence time as a suitable starting point.
-    pub fn set_range_max_convergence_time(&mut self, time_ms: u8) -> Result<(), Error<()>> {
+    pub fn set_range_max_convergence_time(
+        &mut self,
+        time: Milliseconds,
+    ) -> Result<(), Error<()>> {
+        let time_ms = time.0;
         if time_ms < 2 || time_ms > 63 {
-            return Err(Error::InvalidConfigurationValue(time_ms as u16));
+            return Err(Error::InvalidConfigurationValue(time_ms));
         }
-        self.range_max_convergence_time = time_ms;
+        self.range_max_convergence_time = time_ms as u8;
         Ok(())
     }
 
@@ -138,9 +145,10 @@ impl Config {
     /// allowable full ranging cycle period.
     pub fn set_range_inter_measurement_period(
         &mut self,
-        time_ms: u16,
+        time: Milliseconds,
     ) -> Result<(), Error<()>> {
-        let min_eq_val = ((self.range_max_convergence_time + 5) as f32 / 0.9) as u16;
+        let time_ms = time.0;
+        let min_eq_val = (self.range_max_convergence_time as u16 + 5) * 10 / 9;
         let min = if 10 < min_eq_val { min_eq_val } else { 10 };
         if time_ms % 10 != 0 || time_ms < min || time_ms > 2550 {
             return Err(Error::InvalidConfigurationValue(time_ms));
@@ -234,9 +242,13 @@ impl Config {
     /// The integration period is the time over which a single ambient light
     /// measurement is made. Integration times in the range 50-100ms are
     /// recommended to reduce impact of light flicker from artificial lighting
-    pub fn set_ambient_integration_period(&mut self, time_ms: u16) -> Result<(), Error<()>> {
+    pub fn set_ambient_integration_period(
+        &mut self,
+        time: Milliseconds,
+    ) -> Result<(), Error<()>> {
+        let time_ms = time.0;
         if time_ms < 1 || time_ms > 256 {
-            return Err(Error::InvalidConfigurationValue(time_ms as u16));
+            return Err(Error::InvalidConfigurationValue(time_ms));
         }
         self.ambient_integration_period = time_ms;
         Ok(())
@@ -259,9 +271,10 @@ impl Config {
     /// The interleaved requirement is only checked when the interleaved mode is started.
     pub fn set_ambient_inter_measurement_period(
         &mut self,
-        time_ms: u16,
+        time: Milliseconds,
     ) -> Result<(), Error<()>> {
-        let min_eq_val = ((self.ambient_integration_period as f32 * 1.1) / 0.9) as u16;
+        let time_ms = time.0;
+        let min_eq_val = (self.ambient_integration_period as u32 * 11 / 9) as u16;
         let min = if 10 < min_eq_val { min_eq_val } else { 10 };
         if time_ms % 10 != 0 || time_ms < min || time_ms > 2560 {
             return Err(Error::InvalidConfigurationValue(time_ms));
@@ -324,8 +337,8 @@ impl Config {
     ///
     /// Note: Threshold is in raw device value not lux.
     /// This value will be multiplied by the [ambient_result_scaler](Config::set_ambient_result_scaler) used
-    pub fn set_ambient_low_interrupt_threshold(&mut self, threshold: u16) {
-        self.ambient_low_interrupt_threshold = threshold;
+    pub fn set_ambient_low_interrupt_threshold(&mut self, threshold: RawAmbientCount) {
+        self.ambient_low_interrupt_threshold = threshold.0;
     }
 
     /// Set the high threshold for ambient interrupt.
@@ -334,8 +347,8 @@ impl Config {
     ///
     /// Note: Threshold is in raw device value not lux.
     /// This value will be multiplied by the [ambient_result_scaler](Config::set_ambient_result_scaler) used
-    pub fn set_ambient_high_interrupt_threshold(&mut self, threshold: u16) {
-        self.ambient_high_interrupt_threshold = threshold;
+    pub fn set_ambient_high_interrupt_threshold(&mut self, threshold: RawAmbientCount) {
+        self.ambient_high_interrupt_threshold = threshold.0;
     }
 
     /// Set the i2c address for the initial connection
@@ -11,7 +11,10 @@ fn bitwise_or_interrupt_modes() {
 fn set_range_max_convergence_time_value_too_small() {
     let mut config = Config::new();
     assert_eq!(
-        config.set_range_max_convergence_time(1).err().unwrap(),
+        config
+            .set_range_max_convergence_time(Milliseconds(1))
+            .err()
+            .unwrap(),
         Error::InvalidConfigurationValue(1)
     )
 }
@@ -20,7 +23,10 @@ fn set_range_max_convergence_time_value_too_small() {
 fn set_range_max_convergence_time_value_too_high() {
     let mut config = Config::new();
     assert_eq!(
-        config.set_range_max_convergence_time(64).err().unwrap(),
+        config
+            .set_range_max_convergence_time(Milliseconds(64))
+            .err()
+            .unwrap(),
         Error::InvalidConfigurationValue(64)
     )
 }
@@ -28,5 +34,34 @@ fn set_range_max_convergence_time_value_too_high() {
 #[test]
 fn set_range_max_convergence_time_value_valid() {
     let mut config = Config::new();
-    assert_eq!(config.set_range_max_convergence_time(20), Ok(()))
+    assert_eq!(
+        config.set_range_max_convergence_time(Milliseconds(20)),
+        Ok(())
+    )
+}
+
+#[test]
+fn set_range_inter_measurement_period_below_convergence_minimum() {
+    let mut config = Config::new();
+    assert_eq!(
+        config.set_range_inter_measurement_period(Milliseconds(50)),
+        Err(Error::InvalidConfigurationValue(50))
+    );
+    assert_eq!(
+        config.set_range_inter_measurement_period(Milliseconds(60)),
+        Ok(())
+    )
+}
+
+#[test]
+fn set_ambient_inter_measurement_period_below_integration_minimum() {
+    let mut config = Config::new();
+    assert_eq!(
+        config.set_ambient_inter_measurement_period(Milliseconds(120)),
+        Err(Error::InvalidConfigurationValue(120))
+    );
+    assert_eq!(
+        config.set_ambient_inter_measurement_period(Milliseconds(130)),
+        Ok(())
+    )
 }
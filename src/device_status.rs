@@ -1,12 +1,21 @@
+use core::convert::TryFrom;
+
 use embedded_hal::digital::OutputPin;
-use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
 
 use super::VL6180X;
 use crate::{
+    config::DEFAULT_I2C_ADDRESS,
     error::{Error, Error2},
-    register::{Register8Bit::*, SysInterruptClearCode},
+    register::{
+        InterruptErrorCode, InterruptStatus, Register8Bit::*, SysInterruptClearCode,
+        INTERRUPT_ERROR_MASK,
+    },
 };
 
+/// Time the `x_shutdown_pin` is held low to reset the device.
+pub(crate) const HARDWARE_RESET_PULSE_US: u32 = 1_000;
+
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
 where
     I2C: I2c<Error = E>,
@@ -16,11 +25,26 @@ where
         Ok(id)
     }
 
-    pub(crate) async fn read_interrupt_status_direct(&mut self) -> Result<u8, Error<E>> {
+    pub(crate) async fn read_interrupt_status_direct(
+        &mut self,
+    ) -> Result<InterruptStatus, Error<E>> {
+        let status = self
+            .read_named_register(RESULT__INTERRUPT_STATUS_GPIO)
+            .await?;
+        InterruptStatus::try_from(status).map_err(Error::UnknownRegisterCode)
+    }
+
+    pub(crate) async fn read_device_errors_direct(
+        &mut self,
+    ) -> Result<InterruptErrorCode, Error<E>> {
         let status = self
             .read_named_register(RESULT__INTERRUPT_STATUS_GPIO)
             .await?;
-        return Ok(status);
+        if status & INTERRUPT_ERROR_MASK != 0 {
+            // Also an error code that cannot be decoded is cleared
+            self.clear_error_interrupt_direct().await?;
+        }
+        InterruptErrorCode::try_from(status).map_err(|_| Error::UnknownRegisterCode(status))
     }
 
     pub(crate) async fn clear_error_interrupt_direct(&mut self) -> Result<(), Error<E>> {
@@ -77,6 +101,7 @@ where
         x_shutdown_pin.set_low().map_err(|e| Error::GpioPinError(e))
     }
 
+    /// Powers on and initializes the device.
     pub(crate) async fn power_on_and_init_direct<PE, P: OutputPin<Error = PE>>(
         &mut self,
         x_shutdown_pin: &mut P,
@@ -84,26 +109,103 @@ where
         x_shutdown_pin
             .set_high()
             .map_err(|e| Error2::GpioPinError(e))?;
-        self.wait_device_booted()
-            .await
-            .map_err(|e| Error2::<E, PE>::BusError(e))?;
-        self.init_hardware()
+
+        let address = self.config.address;
+        self.init_powered_on_direct(address, false).await
+    }
+
+    /// Initializes the device after it was powered on and sets its I2C address to
+    /// `address`.
+    ///
+    /// The device always boots with the default I2C address, so the address is only
+    /// changed after the initialization. With `resume` an initialization that was
+    /// interrupted is carried out again, although the device may have left its fresh
+    /// out of reset state already.
+    pub(crate) async fn init_powered_on_direct<PE>(
+        &mut self,
+        address: u8,
+        resume: bool,
+    ) -> Result<(), Error2<E, PE>> {
+        self.config.address = DEFAULT_I2C_ADDRESS;
+        let result = self.init_booted_device(address, resume).await;
+        // Keep the address to restore if this gets retried after an error
+        self.config.address = address;
+        result
+    }
+
+    async fn init_booted_device<PE>(
+        &mut self,
+        address: u8,
+        resume: bool,
+    ) -> Result<(), Error2<E, PE>> {
+        let fresh_out_of_reset = if resume {
+            self.wait_device_answers().await?
+        } else {
+            self.wait_device_booted().await?;
+            let model_id = self.read_named_register(IDENTIFICATION__MODEL_ID).await?;
+            if model_id != 0xB4 {
+                return Err(Error2::InvalidDevice(model_id));
+            }
+            0x01
+        };
+        if fresh_out_of_reset == 0x01 {
+            self.init_hardware().await?;
+        } else {
+            // The part-to-part offset was read before, the register may be rescaled
+            self.init_registers().await?;
+        }
+        if address != DEFAULT_I2C_ADDRESS {
+            self.write_only_named_register(I2C_SLAVE__DEVICE_ADDRESS, address)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Pulses the `x_shutdown_pin` low to reset the device, then powers it back on
+    /// and initializes it.
+    pub(crate) async fn hardware_reset_direct<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        x_shutdown_pin
+            .set_low()
+            .map_err(|e| Error2::GpioPinError(e))?;
+        delay.delay_us(HARDWARE_RESET_PULSE_US).await;
+        self.power_on_and_init_direct(x_shutdown_pin).await
+    }
+
+    /// Polls SYSTEM__FRESH_OUT_OF_RESET until the device has booted.
+    /// The device does not answer while booting, so bus errors are ignored.
+    pub(crate) async fn wait_device_booted<PE>(&mut self) -> Result<(), Error2<E, PE>> {
+        let mut c = 0;
+        while self
+            .read_named_register(SYSTEM__FRESH_OUT_OF_RESET)
             .await
-            .map_err(|e| Error2::<E, PE>::BusError(e))?;
+            .ok() !=
+            Some(0x01)
+        {
+            c += 1;
+            if c == self.config.poll_max_loop {
+                return Err(Error2::Timeout);
+            }
+        }
         Ok(())
     }
 
-    async fn wait_device_booted(&mut self) -> Result<(), E> {
+    /// Polls SYSTEM__FRESH_OUT_OF_RESET until the device answers, returning its value.
+    async fn wait_device_answers<PE>(&mut self) -> Result<u8, Error2<E, PE>> {
+        let mut c = 0;
         loop {
-            match self.read_named_register(SYSTEM__FRESH_OUT_OF_RESET).await {
-                Ok(result) => {
-                    if result == 0x01 {
-                        break;
-                    }
-                }
-                Err(_) => (),
+            if let Ok(fresh_out_of_reset) =
+                self.read_named_register(SYSTEM__FRESH_OUT_OF_RESET).await
+            {
+                return Ok(fresh_out_of_reset);
+            }
+            c += 1;
+            if c == self.config.poll_max_loop {
+                return Err(Error2::Timeout);
             }
         }
-        Ok(())
     }
 }
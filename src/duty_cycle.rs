@@ -0,0 +1,241 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    error::{Error, Error2},
+    mode::{DynamicMode, OperatingMode},
+    Config, Range, VL6180XwPins,
+};
+
+#[cfg(test)]
+mod duty_cycle_tests;
+
+/// Range pre-calibration time, see VL6180X datasheet section 2.7.1 Range timing.
+const RANGE_PRE_CALIBRATION_US: u32 = 3_200;
+/// Fixed part of the readout averaging period, see
+/// [`set_readout_averaging_period_multiplier()`](Config::set_readout_averaging_period_multiplier).
+const READOUT_AVERAGING_BASE_US: u32 = 1_300;
+/// Readout averaging time per unit of the multiplier, in 0.1µs.
+const READOUT_AVERAGING_STEP_TENTH_US: u32 = 645;
+
+/// Current draw and timing figures a [DutyCycledSampler] estimates the average current
+/// from.
+///
+/// The defaults are the typical figures from the VL6180X datasheet, measure the
+/// actual board for a better estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct PowerFigures {
+    ranging_na: u32,
+    standby_na: u32,
+    shutdown_na: u32,
+    wake_up_us: u32,
+}
+
+impl PowerFigures {
+    /// Create new power figures with default values.
+    pub fn new() -> Self {
+        PowerFigures {
+            ranging_na: 1_700_000,
+            standby_na: 1_000,
+            shutdown_na: 1_000,
+            wake_up_us: 5_000,
+        }
+    }
+
+    /// Set the average current while ranging, in nA.
+    ///
+    /// Default = 1.7mA;
+    pub fn set_ranging_na(&mut self, current_na: u32) {
+        self.ranging_na = current_na;
+    }
+
+    /// Set the current while powered on and idle (software standby), in nA.
+    /// Also used while the sensor boots and is initialized.
+    ///
+    /// Default = 1µA;
+    pub fn set_standby_na(&mut self, current_na: u32) {
+        self.standby_na = current_na;
+    }
+
+    /// Set the current while held in reset by the `x_shutdown_pin` (hardware standby),
+    /// in nA.
+    ///
+    /// Default = 1µA;
+    pub fn set_shutdown_na(&mut self, current_na: u32) {
+        self.shutdown_na = current_na;
+    }
+
+    /// Set the time from setting the `x_shutdown_pin` high until the sensor is booted
+    /// and initialized, in µs.
+    ///
+    /// Default = 5000µs, the boot time plus writing the config at 400kHz;
+    pub fn set_wake_up_us(&mut self, time_us: u32) {
+        self.wake_up_us = time_us;
+    }
+}
+
+impl Default for PowerFigures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error of a [DutyCycledSampler].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum SamplerError<E, PE> {
+    /// Powering on or ranging failed.
+    Sensor(Error<E>),
+    /// Setting the `x_shutdown_pin` failed.
+    GpioPinError(PE),
+}
+
+impl<E, PE> SamplerError<E, PE> {
+    fn from_split(error: Result<Error<E>, PE>) -> Self {
+        match error {
+            Ok(e) => SamplerError::Sensor(e),
+            Err(e) => SamplerError::GpioPinError(e),
+        }
+    }
+}
+
+impl<E, PE> From<Error2<E, PE>> for SamplerError<E, PE> {
+    fn from(error: Error2<E, PE>) -> Self {
+        Self::from_split(error.split_pin_error())
+    }
+}
+
+/// Takes one single shot range measurement per period and powers the sensor off in
+/// between.
+///
+/// Each [`sample()`](DutyCycledSampler::sample) waits for the rest of the period,
+/// powers on and initializes the sensor, measures and powers it off again. The delay
+/// has no clock, so the period is kept by subtracting the estimated time the sensor
+/// is active, worst case with the configured
+/// [range_max_convergence_time](Config::set_range_max_convergence_time).
+///
+/// The sensor is only powered off if it stays off for at least as long as it takes to
+/// wake it up. Otherwise the sampler warm starts: the sensor stays powered on, in
+/// software standby, and the initialization is skipped, see
+/// [`is_warm_start()`](DutyCycledSampler::is_warm_start).
+///
+/// The sensor must be either [Ready](OperatingMode::Ready) or
+/// [PoweredOff](OperatingMode::PoweredOff).
+#[derive(Debug)]
+pub struct DutyCycledSampler<I2C, OP, IP, D>
+where
+    I2C: I2c,
+    OP: OutputPin,
+    IP: InputPin,
+{
+    sensor: VL6180XwPins<DynamicMode, I2C, OP, IP>,
+    delay: D,
+    period_ms: u32,
+    figures: PowerFigures,
+    waiting: bool,
+}
+
+impl<I2C, E, OP, PE, IP, D> DutyCycledSampler<I2C, OP, IP, D>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+    D: DelayNs,
+{
+    /// Create a new sampler measuring every `period_ms`, with the default
+    /// [PowerFigures]. The first sample is taken at once.
+    pub fn new(
+        sensor: VL6180XwPins<DynamicMode, I2C, OP, IP>,
+        delay: D,
+        period_ms: u32,
+    ) -> Self {
+        DutyCycledSampler {
+            sensor,
+            delay,
+            period_ms,
+            figures: PowerFigures::new(),
+            waiting: false,
+        }
+    }
+
+    /// Returns the sensor and the delay.
+    pub fn into_inner(self) -> (VL6180XwPins<DynamicMode, I2C, OP, IP>, D) {
+        (self.sensor, self.delay)
+    }
+
+    /// Set the figures the average current is estimated from.
+    pub fn set_power_figures(&mut self, figures: PowerFigures) {
+        self.figures = figures;
+    }
+
+    /// Returns whether the sensor stays powered on between samples, because the period
+    /// is too short to power it off.
+    pub fn is_warm_start(&self) -> bool {
+        let active_us = self.figures.wake_up_us + range_time_us(&self.sensor.vl6180x.config);
+        self.period_us() < active_us + self.figures.wake_up_us
+    }
+
+    /// Estimates the average current of the sensor, in nA.
+    ///
+    /// Assumes each measurement takes the
+    /// [range_max_convergence_time](Config::set_range_max_convergence_time), so it is
+    /// an upper bound when the target is in range.
+    pub fn estimate_average_current_na(&self) -> u32 {
+        let figures = &self.figures;
+        let period_us = self.period_us() as u64;
+        let ranging_us = (range_time_us(&self.sensor.vl6180x.config) as u64).min(period_us);
+        let mut charge = figures.ranging_na as u64 * ranging_us;
+        let idle_us = period_us - ranging_us;
+        if self.is_warm_start() {
+            charge += figures.standby_na as u64 * idle_us;
+        } else {
+            let wake_up_us = (figures.wake_up_us as u64).min(idle_us);
+            charge += figures.standby_na as u64 * wake_up_us;
+            charge += figures.shutdown_na as u64 * (idle_us - wake_up_us);
+        }
+        (charge / period_us.max(1)) as u32
+    }
+
+    /// Waits for the rest of the period, then takes a range measurement.
+    ///
+    /// The sensor is powered off afterwards unless warm starting, also if the
+    /// measurement failed. If powering on failed, the next call carries on.
+    pub async fn sample(&mut self) -> Result<Range, SamplerError<E, PE>> {
+        let warm_start = self.is_warm_start();
+        if self.waiting {
+            let mut active_us = range_time_us(&self.sensor.vl6180x.config);
+            if !warm_start {
+                active_us += self.figures.wake_up_us;
+            }
+            let sleep_us = self.period_us().saturating_sub(active_us);
+            self.delay.delay_us(sleep_us).await;
+        }
+        self.waiting = true;
+
+        if self.sensor.vl6180x.operating_mode() == OperatingMode::PoweredOff {
+            self.sensor.try_power_on_and_init().await?;
+        }
+        let range = self.sensor.vl6180x.try_poll_range_single_blocking().await;
+        if !warm_start {
+            self.sensor
+                .try_power_off()
+                .map_err(|e| SamplerError::from_split(e.split_pin_error()))?;
+        }
+        range.map_err(SamplerError::Sensor)
+    }
+
+    fn period_us(&self) -> u32 {
+        self.period_ms.saturating_mul(1_000)
+    }
+}
+
+/// Worst case time of a single shot range measurement in µs, see VL6180X datasheet
+/// section 2.7.1 Range timing.
+pub(crate) fn range_time_us(config: &Config) -> u32 {
+    RANGE_PRE_CALIBRATION_US +
+        config.range_max_convergence_time as u32 * 1_000 +
+        READOUT_AVERAGING_BASE_US +
+        config.readout_averaging_period_multiplier as u32 * READOUT_AVERAGING_STEP_TENTH_US /
+            10
+}
@@ -0,0 +1,152 @@
+use super::*;
+use crate::{
+    mock::{self, MockDelay, MockI2c, MockPin},
+    units::{Millimeters, Milliseconds},
+};
+
+type Sampler = DutyCycledSampler<MockI2c, MockPin, MockPin, MockDelay>;
+
+fn sampler(period_ms: u32) -> Sampler {
+    let mut vl6180x = mock::sensor(DynamicMode::new());
+    vl6180x.try_power_off(&mut MockPin::new(true)).unwrap();
+    let sensor = VL6180XwPins {
+        vl6180x,
+        x_shutdown_pin: MockPin::new(false),
+        interrupt_pin: MockPin::new(false),
+    };
+    DutyCycledSampler::new(sensor, MockDelay::default(), period_ms)
+}
+
+fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    embassy_futures::block_on(future)
+}
+
+/// Powers the mock device up from reset with a range result of `raw`.
+fn boot_with_range(sampler: &mut Sampler, raw: u8) {
+    let com = &mut sampler.sensor.vl6180x.com;
+    com.reset();
+    com.set(0x04F, 0b00_000_100);
+    com.set(0x062, raw);
+}
+
+#[test]
+fn range_time_follows_the_config() {
+    let mut config = Config::new();
+    // 3.2ms + 49ms + 1.3ms + 48 * 64.5µs
+    assert_eq!(range_time_us(&config), 56_596);
+    config
+        .set_range_max_convergence_time(Milliseconds(30))
+        .unwrap();
+    config.set_readout_averaging_period_multiplier(0);
+    assert_eq!(range_time_us(&config), 34_500);
+}
+
+#[test]
+fn powers_on_measures_and_powers_off_every_period() {
+    let mut sampler = sampler(1_000);
+    assert!(!sampler.is_warm_start());
+
+    boot_with_range(&mut sampler, 20);
+    assert_eq!(block_on(sampler.sample()), Ok(Range::Mm(Millimeters(20))));
+    assert_eq!(sampler.delay.total_ns, 0);
+    assert_eq!(sampler.sensor.x_shutdown_pin.history, [true, false]);
+
+    boot_with_range(&mut sampler, 30);
+    assert_eq!(block_on(sampler.sample()), Ok(Range::Mm(Millimeters(30))));
+    // The period minus waking up and ranging
+    assert_eq!(sampler.delay.total_ns, (1_000_000 - 5_000 - 56_596) * 1_000);
+    assert_eq!(
+        sampler.sensor.x_shutdown_pin.history,
+        [true, false, true, false]
+    );
+    assert_eq!(
+        sampler.sensor.vl6180x.operating_mode(),
+        OperatingMode::PoweredOff
+    );
+}
+
+#[test]
+fn warm_start_keeps_the_sensor_initialized() {
+    let mut sampler = sampler(60);
+    assert!(sampler.is_warm_start());
+
+    boot_with_range(&mut sampler, 20);
+    block_on(sampler.sample()).unwrap();
+    let init_writes = sampler.sensor.vl6180x.com.writes.len();
+    sampler.sensor.vl6180x.com.set(0x04F, 0b00_000_100);
+    block_on(sampler.sample()).unwrap();
+
+    assert_eq!(sampler.sensor.x_shutdown_pin.history, [true]);
+    assert_eq!(
+        sampler.sensor.vl6180x.operating_mode(),
+        OperatingMode::Ready
+    );
+    // Only the single shot start and the interrupt clear
+    assert_eq!(
+        sampler.sensor.vl6180x.com.writes[init_writes..],
+        [(0x018, 0b01), (0x015, 0b001)]
+    );
+    assert_eq!(sampler.delay.total_ns, (60_000 - 56_596) * 1_000);
+}
+
+#[test]
+fn powers_off_after_a_failed_measurement() {
+    let mut sampler = sampler(1_000);
+    sampler.sensor.vl6180x.config.set_poll_max_loop(3);
+    sampler.sensor.vl6180x.com.reset();
+    assert_eq!(
+        block_on(sampler.sample()),
+        Err(SamplerError::Sensor(Error::Timeout))
+    );
+    assert!(!sampler.sensor.x_shutdown_pin.is_high);
+
+    boot_with_range(&mut sampler, 40);
+    // The device does not answer while booting
+    sampler.sensor.vl6180x.com.bus_failures = 3;
+    assert_eq!(
+        block_on(sampler.sample()),
+        Err(SamplerError::Sensor(Error::Timeout))
+    );
+    assert_eq!(
+        sampler.sensor.vl6180x.operating_mode(),
+        OperatingMode::PoweredOff
+    );
+    // The next sample carries on booting
+    assert_eq!(block_on(sampler.sample()), Ok(Range::Mm(Millimeters(40))));
+    assert!(!sampler.sensor.x_shutdown_pin.is_high);
+}
+
+#[test]
+fn estimates_the_average_current() {
+    let mut cold = sampler(1_000);
+    // 1.7mA while ranging, 1µA for the rest of the second
+    assert_eq!(
+        cold.estimate_average_current_na(),
+        ((1_700_000 * 56_596_u64 + 1_000 * (1_000_000 - 56_596)) / 1_000_000) as u32
+    );
+
+    let mut figures = PowerFigures::new();
+    figures.set_shutdown_na(100);
+    cold.set_power_figures(figures);
+    assert_eq!(
+        cold.estimate_average_current_na(),
+        ((1_700_000 * 56_596_u64 + 1_000 * 5_000 + 100 * (1_000_000 - 56_596 - 5_000)) /
+            1_000_000) as u32
+    );
+
+    // A shorter convergence time lowers the estimate
+    let before = cold.estimate_average_current_na();
+    cold.sensor
+        .vl6180x
+        .config
+        .set_range_max_convergence_time(Milliseconds(30))
+        .unwrap();
+    assert!(cold.estimate_average_current_na() < before);
+
+    // Warm starting keeps the standby current
+    let warm = sampler(60);
+    assert_eq!(
+        warm.estimate_average_current_na(),
+        ((1_700_000 * 56_596_u64 + 1_000 * (60_000 - 56_596)) / 60_000) as u32
+    );
+}
@@ -1,5 +1,8 @@
 use crate::mode;
 pub use crate::register::{AmbientStatusErrorCode, RangeStatusErrorCode};
+
+#[cfg(test)]
+mod error_tests;
 /// MPU Error
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
@@ -28,25 +31,199 @@ pub enum Error<E> {
     InvalidMethod(mode::dynamic::OperatingMode),
     /// Error when setting pin output state.
     GpioPinError(E),
+    /// The device reported a laser safety error interrupt.
+    /// Cleared with [`read_device_errors()`](crate::VL6180X::read_device_errors).
+    LaserSafetyError,
+    /// The device reported a PLL error interrupt.
+    /// Cleared with [`read_device_errors()`](crate::VL6180X::read_device_errors).
+    PllError,
 }
 /// Test
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub enum Error2<E, F> {
+    /// WHO_AM_I returned invalid value (returned value is argument).
+    InvalidDevice(u8),
     /// Underlying bus error.
     BusError(E),
+    /// Timeout.
+    Timeout,
     /// DynamicMode method call invalid for current operating mode.
     InvalidMethod(mode::dynamic::OperatingMode),
     /// Error when setting pin output state.
     GpioPinError(F),
 }
 
+/// How serious an error is, and whether retrying the failed call can help.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Severity {
+    /// A condition that can clear on its own, like a measurement not being ready yet,
+    /// the target or light level being out of range or a glitch on the bus.
+    /// Retrying the same call can succeed.
+    Transient,
+    /// A device fault the sensor does not recover from on its own.
+    /// Needs a hardware reset, see [`recover()`](crate::VL6180X::recover).
+    DeviceFault,
+    /// Caused by how the driver is used or configured, or by the wrong device being
+    /// connected. Retrying will not succeed.
+    Fatal,
+}
+
+impl<E, F> Error2<E, F> {
+    /// Returns the [Severity] of the error.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error2::BusError(_) | Error2::Timeout => Severity::Transient,
+            Error2::InvalidDevice(_) | Error2::InvalidMethod(_) | Error2::GpioPinError(_) => {
+                Severity::Fatal
+            }
+        }
+    }
+
+    /// Returns whether retrying the failed call can succeed.
+    pub fn is_transient(&self) -> bool {
+        self.severity() == Severity::Transient
+    }
+}
+
+impl<E, PE> Error2<E, PE> {
+    /// Splits off the error of the `x_shutdown_pin`, for errors that report the sensor
+    /// [Error] and the pin error separately.
+    pub(crate) fn split_pin_error(self) -> Result<Error<E>, PE> {
+        match self {
+            Error2::InvalidDevice(id) => Ok(Error::InvalidDevice(id)),
+            Error2::BusError(e) => Ok(Error::BusError(e)),
+            Error2::Timeout => Ok(Error::Timeout),
+            Error2::InvalidMethod(mode) => Ok(Error::InvalidMethod(mode)),
+            Error2::GpioPinError(e) => Err(e),
+        }
+    }
+}
+
 impl<E, F> From<E> for Error2<E, F> {
     fn from(error: E) -> Self {
         Error2::BusError(error)
     }
 }
 
+impl<E> Error<E> {
+    /// Returns whether the error is a device or bus fault that a hardware reset with
+    /// [`recover()`](crate::VL6180X::recover) can fix, if it keeps happening.
+    ///
+    /// These are all [Severity::DeviceFault] errors, and bus errors and timeouts
+    /// waiting for the device, which are [Severity::Transient] since retrying the call
+    /// can succeed.
+    pub fn requires_hardware_reset(&self) -> bool {
+        match self {
+            Error::BusError(_) | Error::Timeout => true,
+            _ => self.severity() == Severity::DeviceFault,
+        }
+    }
+
+    /// Returns the [Severity] of the error.
+    ///
+    /// Bus errors and timeouts are [Severity::Transient], if they keep happening
+    /// the device needs a reset. Every error that is a [Severity::DeviceFault] or
+    /// may need a reset is reported by
+    /// [`requires_hardware_reset()`](Error::requires_hardware_reset).
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error::BusError(_) | Error::Timeout | Error::ResultNotReady => {
+                Severity::Transient
+            }
+            Error::RangeStatusError(code) => code.severity(),
+            Error::AmbientStatusError(code) => code.severity(),
+            Error::UnknownRegisterCode(_) | Error::LaserSafetyError | Error::PllError => {
+                Severity::DeviceFault
+            }
+            Error::InvalidDevice(_) |
+            Error::InvalidAddress(_) |
+            Error::InvalidConfigurationValue(_) |
+            Error::InvalidMethod(_) |
+            Error::GpioPinError(_) => Severity::Fatal,
+        }
+    }
+
+    /// Returns whether retrying the failed call can succeed.
+    pub fn is_transient(&self) -> bool {
+        self.severity() == Severity::Transient
+    }
+
+    /// Splits off the pin error of a method that only sets a pin, like
+    /// [`try_power_off()`](crate::VL6180X::try_power_off), the other errors are
+    /// passed on as a sensor [Error].
+    pub(crate) fn split_pin_error<SE>(self) -> Result<Error<SE>, E> {
+        match self {
+            Error::BusError(e) | Error::GpioPinError(e) => Err(e),
+            Error::InvalidDevice(id) => Ok(Error::InvalidDevice(id)),
+            Error::Timeout => Ok(Error::Timeout),
+            Error::InvalidAddress(address) => Ok(Error::InvalidAddress(address)),
+            Error::InvalidConfigurationValue(value) => {
+                Ok(Error::InvalidConfigurationValue(value))
+            }
+            Error::ResultNotReady => Ok(Error::ResultNotReady),
+            Error::RangeStatusError(code) => Ok(Error::RangeStatusError(code)),
+            Error::AmbientStatusError(code) => Ok(Error::AmbientStatusError(code)),
+            Error::UnknownRegisterCode(code) => Ok(Error::UnknownRegisterCode(code)),
+            Error::InvalidMethod(mode) => Ok(Error::InvalidMethod(mode)),
+            Error::LaserSafetyError => Ok(Error::LaserSafetyError),
+            Error::PllError => Ok(Error::PllError),
+        }
+    }
+}
+
+impl RangeStatusErrorCode {
+    /// Returns the [Severity] of the range status.
+    ///
+    /// The VCSEL and PLL system errors are [Severity::DeviceFault], the other codes are
+    /// environmental conditions like no target, too much ambient light or the target
+    /// being out of range, and are [Severity::Transient].
+    /// [RangeStatusErrorCode::NoError] is not reported as an error and is classified as
+    /// [Severity::Transient].
+    pub fn severity(&self) -> Severity {
+        use RangeStatusErrorCode::*;
+        match self {
+            VcselContinuityTest | VcselWatchdogTest | VcselWatchdog | Pll1Lock | Pll2Lock => {
+                Severity::DeviceFault
+            }
+            NoError |
+            EarlyConvergenceEstimate |
+            MaxConvergence |
+            RangeIgnore |
+            MaxSignalToNoiseRatio |
+            RawRangingAlgoUnderflow |
+            RawRangingAlgoOverflow |
+            RangingAlgoUnderflow |
+            RangingAlgoOverflow => Severity::Transient,
+        }
+    }
+
+    /// Returns whether a new measurement can succeed.
+    pub fn is_transient(&self) -> bool {
+        self.severity() == Severity::Transient
+    }
+}
+
+impl AmbientStatusErrorCode {
+    /// Returns the [Severity] of the ambient light status.
+    ///
+    /// Overflow and underflow depend on the light level, so all codes are
+    /// [Severity::Transient].
+    pub fn severity(&self) -> Severity {
+        match self {
+            AmbientStatusErrorCode::NoError |
+            AmbientStatusErrorCode::Overflow |
+            AmbientStatusErrorCode::Underflow => Severity::Transient,
+        }
+    }
+
+    /// Returns whether a new measurement can succeed.
+    pub fn is_transient(&self) -> bool {
+        self.severity() == Severity::Transient
+    }
+}
+
 impl<E> From<E> for Error<E> {
     fn from(error: E) -> Self {
         Error::BusError(error)
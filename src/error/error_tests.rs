@@ -0,0 +1,120 @@
+use super::*;
+
+#[test]
+fn system_errors_require_hardware_reset() {
+    use RangeStatusErrorCode::*;
+    for code in [
+        VcselContinuityTest,
+        VcselWatchdogTest,
+        VcselWatchdog,
+        Pll1Lock,
+        Pll2Lock,
+    ] {
+        assert!(Error::<()>::RangeStatusError(code).requires_hardware_reset());
+    }
+    assert!(Error::BusError(()).requires_hardware_reset());
+    assert!(Error::<()>::Timeout.requires_hardware_reset());
+    assert!(Error::<()>::LaserSafetyError.requires_hardware_reset());
+    assert!(Error::<()>::PllError.requires_hardware_reset());
+    assert!(Error::<()>::UnknownRegisterCode(0xFF).requires_hardware_reset());
+}
+
+#[test]
+fn measurement_conditions_do_not_require_hardware_reset() {
+    use RangeStatusErrorCode::*;
+    for code in [MaxConvergence, MaxSignalToNoiseRatio, RangingAlgoOverflow] {
+        assert!(!Error::<()>::RangeStatusError(code).requires_hardware_reset());
+    }
+    assert!(!Error::<()>::ResultNotReady.requires_hardware_reset());
+    assert!(!Error::<()>::InvalidConfigurationValue(0).requires_hardware_reset());
+}
+
+#[test]
+fn range_status_severity() {
+    use RangeStatusErrorCode::*;
+    for code in [
+        VcselContinuityTest,
+        VcselWatchdogTest,
+        VcselWatchdog,
+        Pll1Lock,
+        Pll2Lock,
+    ] {
+        assert_eq!(code.severity(), Severity::DeviceFault);
+        assert!(!code.is_transient());
+    }
+    for code in [
+        EarlyConvergenceEstimate,
+        MaxConvergence,
+        RangeIgnore,
+        MaxSignalToNoiseRatio,
+        RawRangingAlgoUnderflow,
+        RawRangingAlgoOverflow,
+        RangingAlgoUnderflow,
+        RangingAlgoOverflow,
+    ] {
+        assert!(code.is_transient());
+    }
+}
+
+#[test]
+fn error_severity() {
+    assert!(Error::BusError(()).is_transient());
+    assert!(Error::<()>::Timeout.is_transient());
+    assert!(Error::<()>::ResultNotReady.is_transient());
+    assert!(Error::<()>::AmbientStatusError(AmbientStatusErrorCode::Overflow).is_transient());
+    assert_eq!(
+        Error::<()>::RangeStatusError(RangeStatusErrorCode::Pll2Lock).severity(),
+        Severity::DeviceFault
+    );
+    assert_eq!(
+        Error::<()>::LaserSafetyError.severity(),
+        Severity::DeviceFault
+    );
+    assert_eq!(Error::<()>::PllError.severity(), Severity::DeviceFault);
+    assert_eq!(
+        Error::<()>::UnknownRegisterCode(0xFF).severity(),
+        Severity::DeviceFault
+    );
+    assert_eq!(Error::<()>::InvalidDevice(0).severity(), Severity::Fatal);
+    assert_eq!(Error::<()>::InvalidAddress(0).severity(), Severity::Fatal);
+    assert_eq!(
+        Error::<()>::InvalidConfigurationValue(0).severity(),
+        Severity::Fatal
+    );
+    assert_eq!(
+        Error::<()>::InvalidMethod(mode::OperatingMode::Ready).severity(),
+        Severity::Fatal
+    );
+    assert_eq!(Error::GpioPinError(()).severity(), Severity::Fatal);
+}
+
+#[test]
+fn error2_severity() {
+    assert!(Error2::<(), ()>::BusError(()).is_transient());
+    assert!(Error2::<(), ()>::Timeout.is_transient());
+    assert_eq!(
+        Error2::<(), ()>::InvalidDevice(0).severity(),
+        Severity::Fatal
+    );
+    assert_eq!(
+        Error2::<(), ()>::GpioPinError(()).severity(),
+        Severity::Fatal
+    );
+}
+
+#[test]
+fn pin_errors_are_split_off() {
+    assert_eq!(
+        Error2::<u8, char>::GpioPinError('p').split_pin_error(),
+        Err('p')
+    );
+    assert_eq!(
+        Error2::<u8, char>::BusError(1).split_pin_error(),
+        Ok(Error::BusError(1))
+    );
+    assert_eq!(Error::GpioPinError('p').split_pin_error::<u8>(), Err('p'));
+    assert_eq!(
+        Error::<char>::InvalidMethod(mode::OperatingMode::PoweredOff).split_pin_error::<u8>(),
+        Ok(Error::InvalidMethod(mode::OperatingMode::PoweredOff))
+    );
+}
@@ -0,0 +1,161 @@
+use core::convert::TryFrom;
+
+use embedded_hal::i2c::I2c as BlockingI2c;
+use embedded_hal_async::i2c::I2c;
+
+use crate::units::{Millimeters, RawAmbientCount};
+use crate::{
+    error::Error,
+    register::{
+        AmbientStatusErrorCode, InterruptStatus, RangeStatusErrorCode, Register8Bit,
+        SysInterruptClearCode, INTERRUPT_AMBIENT_MASK, INTERRUPT_ERROR_MASK,
+        INTERRUPT_RANGE_MASK,
+    },
+    VL6180X,
+};
+
+#[cfg(test)]
+mod fast_read_tests;
+
+/// RESULT__RANGE_STATUS up to and including RESULT__RANGE_VAL.
+const BURST_LEN: usize = (Register8Bit::RESULT__RANGE_VAL as usize -
+    Register8Bit::RESULT__RANGE_STATUS as usize) +
+    1;
+const RANGE_STATUS: usize = 0;
+const ALS_STATUS: usize = 1;
+const INTERRUPT_STATUS: usize = 2;
+const ALS_VAL: usize = 3;
+const RANGE_VAL: usize = BURST_LEN - 1;
+
+/// Reads the samples the device signalled on GPIO1 with a blocking I2C bus, meant for
+/// an interrupt handler.
+///
+/// A [`read()`](FastReader::read) is one burst read of the status and result
+/// registers, followed by clearing the interrupts it found, if any. It does not wait
+/// for a result, does not allocate and leaves the error handling to the caller, so it
+/// is suited to push the [FastSample] into a queue for the application to process.
+///
+/// Keeps a copy of the I2C address and range scaling, created with
+/// [`VL6180X::fast_reader()`]. Create a new one after changing either.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct FastReader {
+    address: u8,
+    range_scaling: u8,
+}
+
+/// The raw results of a [`FastReader::read()`].
+///
+/// Decode it with [`range()`](FastSample::range) and [`ambient()`](FastSample::ambient),
+/// which only return a result if the interrupt status reported one.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct FastSample {
+    interrupt_status: u8,
+    range_status: u8,
+    ambient_status: u8,
+    range_mm: u16,
+    ambient: u16,
+}
+
+impl<MODE, I2C: I2c> VL6180X<MODE, I2C> {
+    /// Returns a [FastReader] for the current I2C address and range scaling.
+    pub fn fast_reader(&self) -> FastReader {
+        FastReader {
+            address: self.config.address,
+            range_scaling: self.config.range_scaling,
+        }
+    }
+}
+
+impl FastReader {
+    /// Reads the status and result registers and clears the interrupts reported in
+    /// them. `i2c` must reach the same device as the [VL6180X] it was created from.
+    pub fn read<B: BlockingI2c>(&self, i2c: &mut B) -> Result<FastSample, B::Error> {
+        let mut data = [0; BURST_LEN];
+        i2c.write_read(
+            self.address,
+            &(Register8Bit::RESULT__RANGE_STATUS as u16).to_be_bytes(),
+            &mut data,
+        )?;
+        let sample = FastSample {
+            interrupt_status: data[INTERRUPT_STATUS],
+            range_status: data[RANGE_STATUS],
+            ambient_status: data[ALS_STATUS],
+            range_mm: self.range_scaling as u16 * data[RANGE_VAL] as u16,
+            ambient: u16::from_be_bytes([data[ALS_VAL], data[ALS_VAL + 1]]),
+        };
+
+        let mut clear = 0;
+        if sample.range_ready() {
+            clear |= SysInterruptClearCode::Range as u8;
+        }
+        if sample.ambient_ready() {
+            clear |= SysInterruptClearCode::Ambient as u8;
+        }
+        if sample.has_error() {
+            clear |= SysInterruptClearCode::Error as u8;
+        }
+        if clear != 0 {
+            let reg = (Register8Bit::SYSTEM__INTERRUPT_CLEAR as u16).to_be_bytes();
+            i2c.write(self.address, &[reg[0], reg[1], clear])?;
+        }
+        Ok(sample)
+    }
+}
+
+impl FastSample {
+    /// The raw RESULT__INTERRUPT_STATUS_GPIO.
+    pub fn raw_interrupt_status(&self) -> u8 {
+        self.interrupt_status
+    }
+
+    /// The decoded interrupt status, or the raw one if it could not be decoded.
+    pub fn interrupt_status(&self) -> Result<InterruptStatus, u8> {
+        InterruptStatus::try_from(self.interrupt_status)
+    }
+
+    /// Returns whether a range measurement result was read.
+    pub fn range_ready(&self) -> bool {
+        self.interrupt_status & INTERRUPT_RANGE_MASK != 0
+    }
+
+    /// Returns whether an ambient light measurement result was read.
+    pub fn ambient_ready(&self) -> bool {
+        self.interrupt_status & INTERRUPT_AMBIENT_MASK != 0
+    }
+
+    /// Returns whether a device error was reported, see
+    /// [`interrupt_status()`](FastSample::interrupt_status) for which one.
+    pub fn has_error(&self) -> bool {
+        self.interrupt_status & INTERRUPT_ERROR_MASK != 0
+    }
+
+    /// The range if one was read, or [Error::RangeStatusError] for a failed
+    /// measurement.
+    pub fn range(&self) -> Option<Result<Millimeters, Error<()>>> {
+        if !self.range_ready() {
+            return None;
+        }
+        Some(match RangeStatusErrorCode::try_from(self.range_status) {
+            Ok(RangeStatusErrorCode::NoError) => Ok(Millimeters(self.range_mm)),
+            Ok(error) => Err(Error::RangeStatusError(error)),
+            Err(_) => Err(Error::UnknownRegisterCode(self.range_status)),
+        })
+    }
+
+    /// The raw ambient light count if one was read, or [Error::AmbientStatusError] for
+    /// a failed measurement.
+    pub fn ambient(&self) -> Option<Result<RawAmbientCount, Error<()>>> {
+        if !self.ambient_ready() {
+            return None;
+        }
+        Some(
+            match AmbientStatusErrorCode::try_from(self.ambient_status) {
+                Ok(AmbientStatusErrorCode::NoError) => Ok(RawAmbientCount(self.ambient)),
+                Ok(error) => Err(Error::AmbientStatusError(error)),
+                Err(_) => Err(Error::UnknownRegisterCode(self.ambient_status)),
+            },
+        )
+    }
+}
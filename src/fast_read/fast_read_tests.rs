@@ -0,0 +1,72 @@
+use super::*;
+use crate::mock::{self, MockI2c};
+use crate::register::InterruptErrorCode;
+
+fn reader_and_bus() -> (FastReader, MockI2c) {
+    let sensor = mock::sensor(crate::ReadyMode);
+    (sensor.fast_reader(), sensor.com)
+}
+
+#[test]
+fn reads_range_in_one_burst_and_clears_it() {
+    let (reader, mut i2c) = reader_and_bus();
+    i2c.set(0x04F, 0b00_000_100);
+    i2c.set(0x062, 42);
+    let sample = reader.read(&mut i2c).unwrap();
+    assert_eq!(sample.range(), Some(Ok(Millimeters(42))));
+    assert_eq!(sample.ambient(), None);
+    assert_eq!(i2c.transactions, 2);
+    assert_eq!(i2c.writes, [(0x015, 0b001)]);
+    assert_eq!(i2c.registers[0x04F], 0);
+}
+
+#[test]
+fn reads_both_samples_and_errors_with_a_single_clear() {
+    let (reader, mut i2c) = reader_and_bus();
+    i2c.set(0x04F, 0b01_100_100);
+    i2c.set(0x04D, 0b0111_0001);
+    i2c.set(0x050, 0x12);
+    i2c.set(0x051, 0x34);
+    let sample = reader.read(&mut i2c).unwrap();
+    assert!(sample.has_error());
+    assert_eq!(
+        sample.interrupt_status().map(|status| status.error),
+        Ok(InterruptErrorCode::LaserSafetyError)
+    );
+    assert_eq!(
+        sample.range(),
+        Some(Err(Error::RangeStatusError(
+            RangeStatusErrorCode::MaxConvergence
+        )))
+    );
+    assert_eq!(sample.ambient(), Some(Ok(RawAmbientCount(0x1234))));
+    assert_eq!(i2c.writes, [(0x015, 0b111)]);
+}
+
+#[test]
+fn nothing_ready_is_a_single_transaction() {
+    let (reader, mut i2c) = reader_and_bus();
+    let sample = reader.read(&mut i2c).unwrap();
+    assert_eq!(sample.range(), None);
+    assert_eq!(sample.ambient(), None);
+    assert!(!sample.has_error());
+    assert_eq!(i2c.transactions, 1);
+    assert!(i2c.writes.is_empty());
+}
+
+#[test]
+fn uses_the_address_and_range_scaling_of_the_driver() {
+    let mut sensor = mock::sensor(crate::ReadyMode);
+    sensor.config.address = 0x30;
+    sensor.config.range_scaling = 3;
+    let reader = sensor.fast_reader();
+    let mut i2c = sensor.com;
+    i2c.set(0x04F, 0b00_000_100);
+    i2c.set(0x062, 100);
+    assert!(reader.read(&mut i2c).is_err());
+    i2c.device_address = 0x30;
+    assert_eq!(
+        reader.read(&mut i2c).unwrap().range(),
+        Some(Ok(Millimeters(300)))
+    );
+}
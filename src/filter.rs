@@ -0,0 +1,238 @@
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    error::{Error, Severity},
+    mode::{AllowReadMeasurement, ReadyMode},
+    register::RangeStatusErrorCode,
+    units::Millimeters,
+    VL6180X,
+};
+
+#[cfg(test)]
+mod filter_tests;
+
+/// How [RangeFilter] combines the valid samples into one value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum FilterStrategy {
+    /// Median of the valid samples in the window, the mean of the middle two
+    /// for an even number of samples. Best at ignoring single outliers.
+    Median,
+    /// Mean of the valid samples in the window.
+    Mean,
+    /// Exponential moving average over all valid samples, where each new sample is
+    /// weighted by `alpha / 256`. `alpha` = 0 is treated as 1.
+    ///
+    /// The window is still used for [`valid_samples()`](RangeFilter::valid_samples),
+    /// so the filter reports no value once the window holds no valid samples.
+    ExponentialMovingAverage {
+        /// Weight of a new sample, out of 256.
+        alpha: u8,
+    },
+}
+
+/// Fixed-size window of the last `N` range samples, filtered with a [FilterStrategy].
+///
+/// Samples that failed with a [RangeStatusErrorCode] take up a slot in the window
+/// without a value, so stale values age out while there is no target.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RangeFilter<const N: usize> {
+    strategy: FilterStrategy,
+    window: [Option<u16>; N],
+    next: usize,
+    /// Exponential moving average in 1/256 mm
+    average: Option<u32>,
+}
+
+impl<const N: usize> RangeFilter<N> {
+    /// Create a new empty filter.
+    pub fn new(strategy: FilterStrategy) -> Self {
+        RangeFilter {
+            strategy,
+            window: [None; N],
+            next: 0,
+            average: None,
+        }
+    }
+
+    /// Add a range sample, the oldest one leaves the window.
+    /// Returns the filtered value, see [`value()`](RangeFilter::value).
+    pub fn push(
+        &mut self,
+        sample: Result<Millimeters, RangeStatusErrorCode>,
+    ) -> Option<Millimeters> {
+        if N == 0 {
+            return None;
+        }
+        let range_mm = sample.ok().map(|range| range.0);
+        self.window[self.next] = range_mm;
+        self.next = (self.next + 1) % N;
+        if self.valid_samples() == 0 {
+            // Start over from the next valid sample
+            self.average = None;
+        } else if let (Some(range_mm), FilterStrategy::ExponentialMovingAverage { alpha }) =
+            (range_mm, self.strategy)
+        {
+            let sample = (range_mm as u32) << 8;
+            self.average = Some(match self.average {
+                None => sample,
+                Some(average) => {
+                    let alpha = alpha.max(1) as u32;
+                    (average * (256 - alpha) + sample * alpha) >> 8
+                }
+            });
+        }
+        self.value()
+    }
+
+    /// Filtered range in mm, or `None` if there are no valid samples in the window.
+    pub fn value(&self) -> Option<Millimeters> {
+        self.value_mm().map(Millimeters)
+    }
+
+    fn value_mm(&self) -> Option<u16> {
+        let mut values = [0_u16; N];
+        let mut count = 0;
+        for range_mm in self.window.iter().flatten() {
+            values[count] = *range_mm;
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+        let values = &mut values[..count];
+        match self.strategy {
+            FilterStrategy::Median => {
+                values.sort_unstable();
+                let upper = values[count / 2];
+                if count % 2 == 1 {
+                    Some(upper)
+                } else {
+                    let lower = values[count / 2 - 1];
+                    Some((lower as u32 + upper as u32).div_ceil(2) as u16)
+                }
+            }
+            FilterStrategy::Mean => {
+                let sum: u32 = values.iter().map(|v| *v as u32).sum();
+                Some(((sum + count as u32 / 2) / count as u32) as u16)
+            }
+            FilterStrategy::ExponentialMovingAverage { .. } => {
+                self.average.map(|average| ((average + 128) >> 8) as u16)
+            }
+        }
+    }
+
+    /// Number of samples in the window that have a value.
+    pub fn valid_samples(&self) -> usize {
+        self.window.iter().filter(|sample| sample.is_some()).count()
+    }
+
+    /// Empties the window.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.strategy);
+    }
+}
+
+/// Filtered range reading returned by [FilteredReader].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct FilteredRange {
+    /// Filtered range in mm, `None` if there are no valid samples in the window.
+    pub range_mm: Option<Millimeters>,
+    /// Number of samples in the window that have a value.
+    pub valid_samples: usize,
+    /// Range status error of the latest sample, if it was skipped.
+    pub skipped: Option<RangeStatusErrorCode>,
+}
+
+/// Driver wrapper that feeds every range reading through a [RangeFilter].
+///
+/// Readings that fail with a [RangeStatusErrorCode] are skipped. Range status errors
+/// that are device faults (see [Severity::DeviceFault]) and all other errors are
+/// returned without changing the window.
+#[derive(Debug)]
+pub struct FilteredReader<MODE, I2C: I2c, const N: usize> {
+    vl6180x: VL6180X<MODE, I2C>,
+    filter: RangeFilter<N>,
+}
+
+impl<MODE, I2C, E, const N: usize> FilteredReader<MODE, I2C, N>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Wraps the driver with an empty filter.
+    pub fn new(vl6180x: VL6180X<MODE, I2C>, strategy: FilterStrategy) -> Self {
+        FilteredReader {
+            vl6180x,
+            filter: RangeFilter::new(strategy),
+        }
+    }
+
+    /// Returns the driver, e.g. to change mode.
+    pub fn into_inner(self) -> VL6180X<MODE, I2C> {
+        self.vl6180x
+    }
+
+    /// Access to the driver.
+    pub fn vl6180x(&mut self) -> &mut VL6180X<MODE, I2C> {
+        &mut self.vl6180x
+    }
+
+    /// Access to the filter.
+    pub fn filter(&mut self) -> &mut RangeFilter<N> {
+        &mut self.filter
+    }
+
+    fn push(
+        &mut self,
+        result: Result<Millimeters, Error<E>>,
+    ) -> Result<FilteredRange, Error<E>> {
+        let sample = match result {
+            Ok(range_mm) => Ok(range_mm),
+            Err(Error::RangeStatusError(code)) if code.severity() == Severity::Transient => {
+                Err(code)
+            }
+            Err(e) => return Err(e),
+        };
+        let range_mm = self.filter.push(sample);
+        Ok(FilteredRange {
+            range_mm,
+            valid_samples: self.filter.valid_samples(),
+            skipped: sample.err(),
+        })
+    }
+}
+
+impl<MODE, I2C, E, const N: usize> FilteredReader<MODE, I2C, N>
+where
+    I2C: I2c<Error = E>,
+    MODE: AllowReadMeasurement,
+{
+    /// Blocking read of the range measurement, see [`VL6180X::read_range_mm_blocking()`],
+    /// added to the filter.
+    pub async fn read_blocking(&mut self) -> Result<FilteredRange, Error<E>> {
+        let result = self.vl6180x.read_range_mm_blocking().await;
+        self.push(result)
+    }
+
+    /// Non-blocking read of the range measurement, see [`VL6180X::read_range_mm()`],
+    /// added to the filter.
+    /// Returns [Error::ResultNotReady] if the result is not ready.
+    pub async fn read(&mut self) -> Result<FilteredRange, Error<E>> {
+        let result = self.vl6180x.read_range_mm().await;
+        self.push(result)
+    }
+}
+
+impl<I2C, E, const N: usize> FilteredReader<ReadyMode, I2C, N>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Single range measurement, see [`VL6180X::poll_range_mm_single_blocking()`],
+    /// added to the filter.
+    pub async fn poll_single_blocking(&mut self) -> Result<FilteredRange, Error<E>> {
+        let result = self.vl6180x.poll_range_mm_single_blocking().await;
+        self.push(result)
+    }
+}
@@ -0,0 +1,125 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::mock;
+
+const NO_TARGET: Result<Millimeters, RangeStatusErrorCode> =
+    Err(RangeStatusErrorCode::MaxConvergence);
+
+#[test]
+fn median_ignores_outlier() {
+    let mut filter = RangeFilter::<5>::new(FilterStrategy::Median);
+    for sample in [100, 102, 250, 98, 101] {
+        filter.push(Ok(Millimeters(sample)));
+    }
+    assert_eq!(filter.value(), Some(Millimeters(101)));
+}
+
+#[test]
+fn median_of_even_count() {
+    let mut filter = RangeFilter::<4>::new(FilterStrategy::Median);
+    for sample in [10, 40, 20, 31] {
+        filter.push(Ok(Millimeters(sample)));
+    }
+    assert_eq!(filter.value(), Some(Millimeters(26)));
+}
+
+#[test]
+fn mean_of_window() {
+    let mut filter = RangeFilter::<3>::new(FilterStrategy::Mean);
+    for sample in [1000, 10, 20, 31] {
+        filter.push(Ok(Millimeters(sample)));
+    }
+    // The first sample has left the window
+    assert_eq!(filter.value(), Some(Millimeters(20)));
+}
+
+#[test]
+fn exponential_moving_average() {
+    let mut filter =
+        RangeFilter::<2>::new(FilterStrategy::ExponentialMovingAverage { alpha: 128 });
+    assert_eq!(filter.push(Ok(Millimeters(100))), Some(Millimeters(100)));
+    assert_eq!(filter.push(Ok(Millimeters(200))), Some(Millimeters(150)));
+    assert_eq!(filter.push(NO_TARGET), Some(Millimeters(150)));
+    assert_eq!(filter.push(Ok(Millimeters(50))), Some(Millimeters(100)));
+}
+
+#[test]
+fn skipped_samples_take_up_the_window() {
+    let mut filter = RangeFilter::<3>::new(FilterStrategy::Mean);
+    assert_eq!(filter.push(Ok(Millimeters(10))), Some(Millimeters(10)));
+    assert_eq!(filter.push(NO_TARGET), Some(Millimeters(10)));
+    assert_eq!(filter.valid_samples(), 1);
+    assert_eq!(filter.push(Ok(Millimeters(20))), Some(Millimeters(15)));
+    assert_eq!(filter.push(NO_TARGET), Some(Millimeters(20)));
+    assert_eq!(filter.push(NO_TARGET), Some(Millimeters(20)));
+    assert_eq!(filter.push(NO_TARGET), None);
+    assert_eq!(filter.valid_samples(), 0);
+
+    let mut filter =
+        RangeFilter::<1>::new(FilterStrategy::ExponentialMovingAverage { alpha: 10 });
+    filter.push(Ok(Millimeters(10)));
+    assert_eq!(filter.push(NO_TARGET), None);
+    // The average starts over once the window had no valid samples
+    assert_eq!(filter.push(Ok(Millimeters(200))), Some(Millimeters(200)));
+}
+
+#[test]
+fn reset_empties_window() {
+    let mut filter = RangeFilter::<3>::new(FilterStrategy::Median);
+    filter.push(Ok(Millimeters(10)));
+    filter.reset();
+    assert_eq!(filter.value(), None);
+    assert_eq!(filter.valid_samples(), 0);
+}
+
+#[test]
+fn empty_window_size() {
+    let mut filter = RangeFilter::<0>::new(FilterStrategy::Median);
+    assert_eq!(filter.push(Ok(Millimeters(10))), None);
+}
+
+#[test]
+fn reader_skips_range_status_errors() {
+    let mut reader: FilteredReader<_, _, 4> =
+        FilteredReader::new(mock::sensor(ReadyMode), FilterStrategy::Mean);
+    reader.vl6180x().com.set(0x04F, 0b00_000_100);
+    reader.vl6180x().com.set(0x062, 40);
+    assert_eq!(
+        block_on(reader.poll_single_blocking()),
+        Ok(FilteredRange {
+            range_mm: Some(Millimeters(40)),
+            valid_samples: 1,
+            skipped: None,
+        })
+    );
+
+    reader.vl6180x().com.set(0x04F, 0b00_000_100);
+    reader.vl6180x().com.set(0x04D, 0b0111_0001);
+    assert_eq!(
+        block_on(reader.read()),
+        Ok(FilteredRange {
+            range_mm: Some(Millimeters(40)),
+            valid_samples: 1,
+            skipped: Some(RangeStatusErrorCode::MaxConvergence),
+        })
+    );
+}
+
+#[test]
+fn reader_returns_device_faults_and_bus_errors() {
+    let mut reader: FilteredReader<_, _, 4> =
+        FilteredReader::new(mock::sensor(ReadyMode), FilterStrategy::Median);
+    assert_eq!(block_on(reader.read()), Err(Error::ResultNotReady));
+
+    reader.vl6180x().com.set(0x04F, 0b00_000_100);
+    reader.vl6180x().com.set(0x04D, 0b0001_0001);
+    assert_eq!(
+        block_on(reader.read_blocking()),
+        Err(Error::RangeStatusError(
+            RangeStatusErrorCode::VcselContinuityTest
+        ))
+    );
+    assert_eq!(reader.filter().valid_samples(), 0);
+    assert_eq!(reader.filter().value(), None);
+}
@@ -0,0 +1,332 @@
+use embedded_hal_async::i2c::I2c;
+
+use crate::{error::Error, mode::RangeContinuousMode, units::Millimeters, Range, VL6180X};
+
+#[cfg(test)]
+mod gesture_tests;
+
+/// Timing windows and distances of [GestureRecognizer].
+///
+/// The windows should be several range inter-measurement periods long,
+/// see [`Config::set_range_inter_measurement_period()`](crate::Config::set_range_inter_measurement_period).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GestureConfig {
+    /// A hand is over a sensor when it is at this distance or closer.
+    ///
+    /// Default = 150mm
+    pub near_mm: Millimeters,
+    /// Longest time in ms a hand can be over a sensor for a [Gesture::Tap].
+    ///
+    /// Default = 300ms
+    pub tap_max_ms: u32,
+    /// Time in ms a hand must stay over a sensor for a [Gesture::Hold].
+    ///
+    /// Default = 800ms
+    pub hold_min_ms: u32,
+    /// Longest time in ms between a hand arriving over one sensor and then another for
+    /// a swipe. Taps are reported after this time, once they cannot be part of a swipe.
+    ///
+    /// Default = 300ms
+    pub swipe_max_ms: u32,
+    /// Distance a hand must move towards or away from a sensor, while staying over it,
+    /// for a [Gesture::Down] or [Gesture::Up].
+    ///
+    /// Default = 40mm
+    pub vertical_min_mm: Millimeters,
+}
+
+impl GestureConfig {
+    /// Create new gesture config with default values.
+    pub fn new() -> Self {
+        GestureConfig {
+            near_mm: Millimeters(150),
+            tap_max_ms: 300,
+            hold_min_ms: 800,
+            swipe_max_ms: 300,
+            vertical_min_mm: Millimeters(40),
+        }
+    }
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gestures recognised by [GestureRecognizer].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Gesture {
+    /// A hand briefly came over a sensor and left.
+    Tap,
+    /// A hand stayed over a sensor without moving up or down.
+    Hold,
+    /// A hand moved from a sensor to one with a lower index.
+    SwipeLeft,
+    /// A hand moved from a sensor to one with a higher index.
+    SwipeRight,
+    /// A hand over a sensor moved away from it.
+    Up,
+    /// A hand over a sensor moved towards it.
+    Down,
+}
+
+/// A recognised gesture.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GestureEvent {
+    /// The recognised gesture.
+    pub gesture: Gesture,
+    /// Index of the sensor the gesture ended on.
+    pub sensor: usize,
+    /// Time in ms of the sample that completed the gesture.
+    pub timestamp_ms: u32,
+}
+
+/// Gestures recognised from a single sample, at most two.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GestureEvents {
+    events: [Option<GestureEvent>; 2],
+}
+
+impl GestureEvents {
+    fn push(&mut self, event: GestureEvent) {
+        debug_assert!(self.events[1].is_none(), "more than two gesture events");
+        let slot = if self.events[0].is_none() { 0 } else { 1 };
+        self.events[slot] = Some(event);
+    }
+}
+
+impl Iterator for GestureEvents {
+    type Item = GestureEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events[0].take();
+        self.events[0] = self.events[1].take();
+        event
+    }
+}
+
+/// Hand over a sensor.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+struct Presence {
+    since_ms: u32,
+    start_mm: Millimeters,
+    /// A gesture was already recognised for this presence.
+    done: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+struct PendingTap {
+    sensor: usize,
+    timestamp_ms: u32,
+    deadline_ms: u32,
+}
+
+impl PendingTap {
+    fn event(&self) -> GestureEvent {
+        GestureEvent {
+            gesture: Gesture::Tap,
+            sensor: self.sensor,
+            timestamp_ms: self.timestamp_ms,
+        }
+    }
+}
+
+/// Recognises gestures from the range samples of `SENSORS` sensors.
+///
+/// Swipes need at least two sensors, placed in a row in the order of their index.
+/// Tap, hold, up and down are recognised on every sensor.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GestureRecognizer<const SENSORS: usize> {
+    config: GestureConfig,
+    presence: [Option<Presence>; SENSORS],
+    last_arrival_ms: [Option<u32>; SENSORS],
+    pending_tap: Option<PendingTap>,
+}
+
+impl<const SENSORS: usize> GestureRecognizer<SENSORS> {
+    /// Create a new recognizer.
+    pub fn new(config: GestureConfig) -> Self {
+        GestureRecognizer {
+            config,
+            presence: [None; SENSORS],
+            last_arrival_ms: [None; SENSORS],
+            pending_tap: None,
+        }
+    }
+
+    /// Adds a range sample of the sensor with index `sensor`, measured at `timestamp_ms`
+    /// from any monotonic clock. Samples must be added in the order they were measured.
+    ///
+    /// # Panics
+    ///
+    /// If `sensor` is not less than `SENSORS`.
+    pub fn process(
+        &mut self,
+        sensor: usize,
+        timestamp_ms: u32,
+        range: Range,
+    ) -> GestureEvents {
+        let mut events = GestureEvents::default();
+        if let Some(event) = self.tick(timestamp_ms) {
+            events.push(event);
+        }
+        let near_mm = match range {
+            Range::Mm(mm) if mm <= self.config.near_mm => Some(mm),
+            _ => None,
+        };
+        let event = match (self.presence[sensor], near_mm) {
+            (None, Some(mm)) => self.arrive(sensor, timestamp_ms, mm),
+            (Some(presence), Some(mm)) => self.stay(sensor, timestamp_ms, presence, mm),
+            (Some(presence), None) => self.leave(sensor, timestamp_ms, presence, &mut events),
+            (None, None) => None,
+        };
+        if let Some(gesture) = event {
+            events.push(GestureEvent {
+                gesture,
+                sensor,
+                timestamp_ms,
+            });
+        }
+        events
+    }
+
+    /// Reports a pending tap once its swipe window has passed at `timestamp_ms`.
+    /// Called by [`process()`](GestureRecognizer::process).
+    pub fn tick(&mut self, timestamp_ms: u32) -> Option<GestureEvent> {
+        let pending = self.pending_tap?;
+        if timestamp_ms.wrapping_sub(pending.deadline_ms) as i32 <= 0 {
+            return None;
+        }
+        self.pending_tap = None;
+        Some(pending.event())
+    }
+
+    /// Reads the latest range sample of a sensor in [RangeContinuousMode] with
+    /// [`VL6180X::read_range()`] and processes it.
+    /// If no new sample is ready only pending taps are checked.
+    pub async fn update<I2C, E>(
+        &mut self,
+        sensor: usize,
+        vl6180x: &mut VL6180X<RangeContinuousMode, I2C>,
+        timestamp_ms: u32,
+    ) -> Result<GestureEvents, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        match vl6180x.read_range().await {
+            Ok(range) => Ok(self.process(sensor, timestamp_ms, range)),
+            Err(Error::ResultNotReady) => {
+                let mut events = GestureEvents::default();
+                if let Some(event) = self.tick(timestamp_ms) {
+                    events.push(event);
+                }
+                Ok(events)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn arrive(
+        &mut self,
+        sensor: usize,
+        timestamp_ms: u32,
+        mm: Millimeters,
+    ) -> Option<Gesture> {
+        self.presence[sensor] = Some(Presence {
+            since_ms: timestamp_ms,
+            start_mm: mm,
+            done: false,
+        });
+        let swipe_max_ms = self.config.swipe_max_ms;
+        let from = self
+            .last_arrival_ms
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != sensor)
+            .filter_map(|(other, arrival_ms)| {
+                arrival_ms.map(|arrival_ms| (other, timestamp_ms.wrapping_sub(arrival_ms)))
+            })
+            .filter(|(_, elapsed_ms)| *elapsed_ms <= swipe_max_ms)
+            .min_by_key(|(_, elapsed_ms)| *elapsed_ms)
+            .map(|(other, _)| other);
+        self.last_arrival_ms[sensor] = Some(timestamp_ms);
+
+        let from = from?;
+        self.last_arrival_ms[from] = None;
+        if self.pending_tap.map(|pending| pending.sensor) == Some(from) {
+            self.pending_tap = None;
+        }
+        for presence in self.presence.iter_mut().flatten() {
+            presence.done = true;
+        }
+        if from < sensor {
+            Some(Gesture::SwipeRight)
+        } else {
+            Some(Gesture::SwipeLeft)
+        }
+    }
+
+    fn stay(
+        &mut self,
+        sensor: usize,
+        timestamp_ms: u32,
+        mut presence: Presence,
+        mm: Millimeters,
+    ) -> Option<Gesture> {
+        if presence.done {
+            return None;
+        }
+        let Millimeters(vertical_min_mm) = self.config.vertical_min_mm;
+        let gesture = if mm >=
+            Millimeters(presence.start_mm.0.saturating_add(vertical_min_mm))
+        {
+            Gesture::Up
+        } else if Millimeters(mm.0.saturating_add(vertical_min_mm)) <= presence.start_mm {
+            Gesture::Down
+        } else if timestamp_ms.wrapping_sub(presence.since_ms) >= self.config.hold_min_ms {
+            Gesture::Hold
+        } else {
+            return None;
+        };
+        presence.done = true;
+        self.presence[sensor] = Some(presence);
+        Some(gesture)
+    }
+
+    fn leave(
+        &mut self,
+        sensor: usize,
+        timestamp_ms: u32,
+        presence: Presence,
+        events: &mut GestureEvents,
+    ) -> Option<Gesture> {
+        self.presence[sensor] = None;
+        if presence.done ||
+            timestamp_ms.wrapping_sub(presence.since_ms) > self.config.tap_max_ms
+        {
+            return None;
+        }
+        if SENSORS < 2 {
+            return Some(Gesture::Tap);
+        }
+        // Wait until the tap cannot be the start of a swipe, reporting an earlier tap
+        // that is still waiting
+        let replaced = self.pending_tap.replace(PendingTap {
+            sensor,
+            timestamp_ms,
+            deadline_ms: presence.since_ms.wrapping_add(self.config.swipe_max_ms),
+        });
+        if let Some(pending) = replaced {
+            events.push(pending.event());
+        }
+        None
+    }
+}
@@ -0,0 +1,154 @@
+extern crate std;
+
+use embassy_futures::block_on;
+use std::vec::Vec;
+
+use super::*;
+use crate::mock;
+
+const FAR: Option<u16> = None;
+
+/// Feeds a recorded sequence of `(timestamp_ms, sensor, range_mm)` samples, where
+/// `None` is no target, and returns the recognised gestures.
+fn replay<const SENSORS: usize>(samples: &[(u32, usize, Option<u16>)]) -> Vec<GestureEvent> {
+    let mut recognizer = GestureRecognizer::<SENSORS>::new(GestureConfig::new());
+    let mut events = Vec::new();
+    for (timestamp_ms, sensor, range_mm) in samples {
+        let range = range_mm.map_or(Range::NoTarget, |mm| Range::Mm(Millimeters(mm)));
+        events.extend(recognizer.process(*sensor, *timestamp_ms, range));
+    }
+    events
+}
+
+/// Samples of one sensor every 20ms, starting at `start_ms`.
+fn every_20ms(
+    start_ms: u32,
+    sensor: usize,
+    ranges: &[Option<u16>],
+) -> Vec<(u32, usize, Option<u16>)> {
+    ranges
+        .iter()
+        .enumerate()
+        .map(|(i, range)| (start_ms + 20 * i as u32, sensor, *range))
+        .collect()
+}
+
+fn event(gesture: Gesture, sensor: usize, timestamp_ms: u32) -> GestureEvent {
+    GestureEvent {
+        gesture,
+        sensor,
+        timestamp_ms,
+    }
+}
+
+#[test]
+fn tap_on_single_sensor() {
+    let samples = every_20ms(0, 0, &[FAR, Some(80), Some(75), Some(82), FAR, FAR]);
+    assert_eq!(replay::<1>(&samples), [event(Gesture::Tap, 0, 80)]);
+}
+
+#[test]
+fn slow_tap_is_not_a_tap() {
+    let mut ranges = [Some(80); 20];
+    ranges[19] = FAR;
+    assert_eq!(replay::<1>(&every_20ms(0, 0, &ranges)), []);
+}
+
+#[test]
+fn hold_is_reported_once_while_present() {
+    let samples = every_20ms(0, 0, &[Some(100); 60]);
+    assert_eq!(replay::<1>(&samples), [event(Gesture::Hold, 0, 800)]);
+}
+
+#[test]
+fn up_and_down() {
+    let samples = every_20ms(0, 0, &[Some(60), Some(70), Some(85), Some(101), Some(130)]);
+    assert_eq!(replay::<1>(&samples), [event(Gesture::Up, 0, 60)]);
+    let samples = every_20ms(0, 0, &[Some(140), Some(120), Some(100), Some(90), FAR]);
+    assert_eq!(replay::<1>(&samples), [event(Gesture::Down, 0, 40)]);
+}
+
+#[test]
+fn swipes_between_two_sensors() {
+    // Hand passes over sensor 0, then sensor 1, samples of both sensors interleaved
+    let left = [FAR, Some(90), Some(90), FAR, FAR, FAR, FAR];
+    let right = [FAR, FAR, FAR, Some(95), Some(95), FAR, FAR];
+    let mut samples: Vec<_> = every_20ms(0, 0, &left);
+    samples.extend(every_20ms(10, 1, &right));
+    samples.sort_by_key(|sample| sample.0);
+
+    assert_eq!(replay::<2>(&samples), [event(Gesture::SwipeRight, 1, 70)]);
+
+    for sample in samples.iter_mut() {
+        sample.1 = 1 - sample.1;
+    }
+    assert_eq!(replay::<2>(&samples), [event(Gesture::SwipeLeft, 0, 70)]);
+}
+
+#[test]
+fn tap_with_two_sensors_waits_for_swipe_window() {
+    let mut samples = every_20ms(0, 0, &[Some(90), Some(90), FAR, FAR]);
+    samples.extend(every_20ms(80, 0, &[FAR; 20]));
+    let mut recognizer = GestureRecognizer::<2>::new(GestureConfig::new());
+    let mut events = Vec::new();
+    for (timestamp_ms, sensor, range_mm) in samples {
+        let range = range_mm.map_or(Range::NoTarget, |mm| Range::Mm(Millimeters(mm)));
+        for event in recognizer.process(sensor, timestamp_ms, range) {
+            events.push((timestamp_ms, event));
+        }
+    }
+    assert_eq!(events, [(320, event(Gesture::Tap, 0, 40))]);
+}
+
+#[test]
+fn second_tap_reports_pending_tap() {
+    let mut samples = every_20ms(0, 0, &[Some(90), Some(90), FAR, Some(90), Some(90), FAR]);
+    samples.extend(every_20ms(120, 0, &[FAR; 20]));
+    assert_eq!(
+        replay::<2>(&samples),
+        [event(Gesture::Tap, 0, 40), event(Gesture::Tap, 0, 100)]
+    );
+}
+
+#[test]
+fn pending_tap_and_new_gesture_in_one_sample() {
+    let mut recognizer = GestureRecognizer::<2>::new(GestureConfig::new());
+    recognizer.process(0, 220, Range::Mm(Millimeters(90)));
+    // Too late after sensor 0 for a swipe
+    recognizer.process(1, 700, Range::Mm(Millimeters(90)));
+    recognizer.process(1, 740, Range::OutOfRange);
+    assert_eq!(
+        recognizer
+            .process(0, 1_000, Range::Mm(Millimeters(90)))
+            .count(),
+        0
+    );
+    let events: Vec<_> = recognizer
+        .process(0, 1_020, Range::Mm(Millimeters(90)))
+        .collect();
+    assert_eq!(
+        events,
+        [event(Gesture::Tap, 1, 740), event(Gesture::Hold, 0, 1_020)]
+    );
+}
+
+#[test]
+fn update_reads_sensor() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    let mut recognizer = GestureRecognizer::<1>::new(GestureConfig::new());
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x062, 100);
+    assert_eq!(
+        block_on(recognizer.update(0, &mut sensor, 0)).map(|e| e.count()),
+        Ok(0)
+    );
+    assert_eq!(
+        block_on(recognizer.update(0, &mut sensor, 900)).map(|e| e.count()),
+        Ok(0)
+    );
+    sensor.com.set(0x04F, 0b00_000_100);
+    let events: Vec<_> = block_on(recognizer.update(0, &mut sensor, 900))
+        .unwrap()
+        .collect();
+    assert_eq!(events, [event(Gesture::Hold, 0, 900)]);
+}
@@ -13,7 +13,7 @@ where
     }
 
     /// Reads an 8-bit register
-    async fn read_register(&mut self, reg: u16) -> Result<u8, E> {
+    pub(super) async fn read_register(&mut self, reg: u16) -> Result<u8, E> {
         let mut data: [u8; 1] = [0];
         let reg: [u8; 2] = reg.to_be_bytes();
 
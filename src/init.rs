@@ -1,9 +1,14 @@
 use embedded_hal_async::i2c::I2c;
 
 use super::VL6180X;
-use crate::register::{
-    Register16Bit::*, Register8Bit::*, SysModeGpio1Polarity, SysModeGpio1Select,
-    AMBIENT_ANALOGUE_GAIN_CODE, RANGE_SCALAR_CODE,
+use crate::{
+    config::{Config, RangeInterruptMode},
+    error::Error,
+    mode::{CurrentOperatingMode, OperatingMode},
+    register::{
+        Register16Bit::*, Register8Bit::*, SysModeGpio1Polarity, SysModeGpio1Select,
+        AMBIENT_ANALOGUE_GAIN_CODE, RANGE_SCALAR_CODE,
+    },
 };
 
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
@@ -17,7 +22,12 @@ where
         self.config.ptp_offset = self
             .read_named_register(SYSRANGE__PART_TO_PART_RANGE_OFFSET)
             .await?;
+        self.init_registers().await
+    }
 
+    /// Writes the mandatory private registers and the config, without reading the
+    /// part-to-part range offset.
+    pub(crate) async fn init_registers(&mut self) -> Result<(), E> {
         self.write_register(0x207, 0x01).await?;
         self.write_register(0x208, 0x01).await?;
         self.write_register(0x096, 0x00).await?;
@@ -57,6 +67,23 @@ where
         Ok(())
     }
 
+    /// Replaces the config and writes it to the device, see
+    /// [`replace_config()`](VL6180X::replace_config).
+    pub(crate) async fn set_config_direct(&mut self, config: &Config) -> Result<(), E> {
+        self.replace_config(config);
+        self.set_configuration().await
+    }
+
+    /// Replaces the config, keeping the part-to-part range offset read during init and
+    /// the I2C address the device answers to.
+    pub(crate) fn replace_config(&mut self, config: &Config) {
+        let ptp_offset = self.config.ptp_offset;
+        let address = self.config.address;
+        self.config = *config;
+        self.config.ptp_offset = ptp_offset;
+        self.config.address = address;
+    }
+
     /// See VL6180X datasheet and application note to understand how the config
     /// values get transformed into the values the registers are set to.
     async fn set_configuration(&mut self) -> Result<(), E> {
@@ -123,6 +150,47 @@ where
     }
 
     async fn set_interrupts(&mut self) -> Result<(), E> {
+        self.set_interrupt_config().await?;
+
+        // Set the thresholds
+        self.write_range_thresholds().await?;
+        self.write_named_register_16bit(
+            SYSALS__THRESH_HIGH,
+            self.config.ambient_high_interrupt_threshold,
+        )
+        .await?;
+        self.write_named_register_16bit(
+            SYSALS__THRESH_LOW,
+            self.config.ambient_low_interrupt_threshold,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Changes the range interrupt mode and thresholds, also in the config.
+    /// The parameters are held while writing so a running measurement
+    /// does not use a mix of old and new values.
+    pub(crate) async fn set_range_interrupt_direct(
+        &mut self,
+        interrupt_mode: RangeInterruptMode,
+        low_threshold: u8,
+        high_threshold: u8,
+    ) -> Result<(), E> {
+        self.config.range_interrupt_mode = interrupt_mode;
+        self.config.range_low_interrupt_threshold = low_threshold;
+        self.config.range_high_interrupt_threshold = high_threshold;
+
+        self.write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x01)
+            .await?;
+        self.set_interrupt_config().await?;
+        self.write_range_thresholds().await?;
+        self.write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x00)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_interrupt_config(&mut self) -> Result<(), E> {
         // Set the interrupt mode
         let interrupt_val =
             self.config.range_interrupt_mode as u8 | self.config.ambient_interrupt_mode as u8;
@@ -144,8 +212,10 @@ where
             )
             .await?;
         }
+        Ok(())
+    }
 
-        // Set the thresholds
+    async fn write_range_thresholds(&mut self) -> Result<(), E> {
         self.write_named_register(
             SYSRANGE__THRESH_HIGH,
             self.config.range_high_interrupt_threshold,
@@ -156,19 +226,125 @@ where
             self.config.range_low_interrupt_threshold,
         )
         .await?;
-        self.write_named_register_16bit(
-            SYSALS__THRESH_HIGH,
-            self.config.ambient_high_interrupt_threshold,
-        )
-        .await?;
-        self.write_named_register_16bit(
-            SYSALS__THRESH_LOW,
-            self.config.ambient_low_interrupt_threshold,
+        Ok(())
+    }
+    /// Changes the range scaling while the device is running, also in the config.
+    /// The range interrupt thresholds are in scaled units, so they are rescaled to keep
+    /// the same distances. The parameters are held while writing so a running
+    /// measurement does not use a mix of old and new values.
+    pub(crate) async fn set_range_result_scaler_direct(
+        &mut self,
+        scaler: u8,
+    ) -> Result<(), Error<E>> {
+        if !(1..=3).contains(&scaler) {
+            return Err(Error::InvalidConfigurationValue(scaler as u16));
+        }
+        let low_threshold =
+            self.rescale_range_threshold(self.config.range_low_interrupt_threshold, scaler);
+        let high_threshold =
+            self.rescale_range_threshold(self.config.range_high_interrupt_threshold, scaler);
+        self.write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x01)
+            .await?;
+        self.set_range_scaling(scaler).await?;
+        if (low_threshold, high_threshold) !=
+            (
+                self.config.range_low_interrupt_threshold,
+                self.config.range_high_interrupt_threshold,
+            )
+        {
+            self.write_named_register(SYSRANGE__THRESH_HIGH, high_threshold)
+                .await?;
+            self.write_named_register(SYSRANGE__THRESH_LOW, low_threshold)
+                .await?;
+        }
+        self.write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x00)
+            .await?;
+        self.config.range_scaling = scaler;
+        self.config.range_low_interrupt_threshold = low_threshold;
+        self.config.range_high_interrupt_threshold = high_threshold;
+        Ok(())
+    }
+
+    /// The range interrupt `threshold` for the same distance with the `scaler`,
+    /// rounded to the nearest value. 255, the end of the range, is kept.
+    fn rescale_range_threshold(&self, threshold: u8, scaler: u8) -> u8 {
+        if threshold == 0xFF {
+            return threshold;
+        }
+        let scaler = scaler as u16;
+        let distance = threshold as u16 * self.config.range_scaling.max(1) as u16;
+        ((distance + scaler / 2) / scaler).min(0xFF) as u8
+    }
+
+    /// Changes the ambient light analogue gain and integration period while the device
+    /// is running, also in the config. The parameters are held while writing so a
+    /// running measurement does not use a mix of old and new values.
+    pub(crate) async fn set_ambient_gain_and_integration_direct(
+        &mut self,
+        gain_level: u8,
+        integration_period: u16,
+    ) -> Result<(), Error<E>>
+    where
+        MODE: CurrentOperatingMode,
+    {
+        if gain_level > 7 {
+            return Err(Error::InvalidConfigurationValue(gain_level as u16));
+        }
+        if !(1..=256).contains(&integration_period) ||
+            integration_period > self.max_ambient_integration_period()
+        {
+            return Err(Error::InvalidConfigurationValue(integration_period));
+        }
+        self.write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x01)
+            .await?;
+        let written = self
+            .write_ambient_gain_and_integration(gain_level, integration_period)
+            .await;
+        // Release the hold also after a failed write, so the device keeps measuring
+        // with the settings it applies
+        let released = self
+            .write_named_register(SYSTEM__GROUPED_PARAMETER_HOLD, 0x00)
+            .await;
+        written?;
+        released?;
+        self.config.ambient_analogue_gain_level = gain_level;
+        self.config.ambient_integration_period = integration_period;
+        Ok(())
+    }
+
+    async fn write_ambient_gain_and_integration(
+        &mut self,
+        gain_level: u8,
+        integration_period: u16,
+    ) -> Result<(), E> {
+        self.write_named_register(
+            SYSALS__ANALOGUE_GAIN,
+            AMBIENT_ANALOGUE_GAIN_CODE[gain_level as usize],
         )
         .await?;
+        self.write_named_register_16bit(SYSALS__INTEGRATION_PERIOD, integration_period - 1)
+            .await
+    }
 
-        Ok(())
+    /// The longest ambient light integration period in ms the inter-measurement period
+    /// allows, in interleaved mode also leaving time for the range measurement:
+    ///
+    /// `integration_period` * 1.1 ≤ `ambient_inter_measurement_period` * 0.9
+    ///
+    /// (`range_max_convergence_time` + 5) + `integration_period` * 1.1
+    /// ≤ `ambient_inter_measurement_period` * 0.9 in interleaved mode
+    pub(crate) fn max_ambient_integration_period(&self) -> u16
+    where
+        MODE: CurrentOperatingMode,
+    {
+        let mut available = self.config.ambient_inter_measurement_period as u32 * 9;
+        if self.mode.current_operating_mode() == OperatingMode::InterleavedContinuous {
+            available = available
+                .saturating_sub((self.config.range_max_convergence_time as u32 + 5) * 10);
+        }
+        (available / 11) as u16
     }
+
     async fn set_range_scaling(&mut self, new_scaling: u8) -> Result<(), E> {
         const DEFAULT_CROSSTALK_VALID_HEIGHT: u8 = 20; // default value of SYSRANGE__CROSSTALK_VALID_HEIGHT
 
@@ -42,7 +42,7 @@
 //!
 //!         loop {
 //!             match tof.poll_range_mm_single_blocking() {
-//!                 Ok(range) => hprintln!("Range Single Poll: {}mm", range).unwrap(),
+//!                 Ok(range) => hprintln!("Range Single Poll: {}mm", range.0).unwrap(),
 //!                 Err(e) => hprintln!("Error reading TOF sensor Single Poll! {:?}", e).unwrap(),
 //!             }
 //!         }
@@ -72,22 +72,74 @@
     warnings
 )]
 #![allow(dead_code)]
+pub use auto_gain::{AmbientAutoRanger, AmbientSample};
+pub use auto_scale::{RangeAutoScaler, ScaledRange};
 pub use config::*;
+pub use duty_cycle::{DutyCycledSampler, PowerFigures, SamplerError};
 use embedded_hal::digital::{InputPin, OutputPin};
 use embedded_hal_async::i2c::I2c;
-pub use error::Error;
+pub use error::{Error, Severity};
+pub use fast_read::{FastReader, FastSample};
+pub use filter::{FilterStrategy, FilteredRange, FilteredReader, RangeFilter};
+#[cfg(feature = "gestures")]
+pub use gesture::{Gesture, GestureConfig, GestureEvent, GestureEvents, GestureRecognizer};
+pub use measurement::*;
 pub use mode::*;
+pub use poller::SingleShotPoller;
+pub use proximity::{ProximityConfig, ProximityDetector, ProximityEvent, ProximityState};
+pub use retry::{Retry, RetryPolicy};
+#[cfg(feature = "service")]
+pub use service::{Command, Event, Publish, SensorService};
+#[cfg(feature = "shared")]
+pub use shared::SharedVL6180X;
+#[cfg(feature = "stream")]
+pub use stream::{DelayPacer, InterruptPacer, Pacer};
+pub use timestamp::{Clock, SampleTagger, Timestamped};
+#[cfg(feature = "float")]
+pub use tracking::{
+    RangeTracker, TrackerConfig, TrackerEstimate, TrackerSample, TrackerUpdate,
+};
+#[cfg(feature = "float")]
+pub use units::Lux;
+pub use units::{Mcps, MilliLux, Millimeters, Milliseconds, RawAmbientCount};
 
-pub use crate::register::ResultInterruptStatusGpioCode;
+pub use crate::register::{
+    AmbientStatusErrorCode, InterruptErrorCode, InterruptEventCode, InterruptStatus,
+    RangeStatusErrorCode, ResultInterruptStatusGpioCode,
+};
+mod auto_gain;
+mod auto_scale;
 mod config;
 mod device_status;
+mod duty_cycle;
 mod error;
+mod fast_read;
+mod filter;
+#[cfg(feature = "gestures")]
+mod gesture;
 mod i2c_interface;
 mod init;
+mod measurement;
+#[cfg(test)]
+mod mock;
 mod mode;
+mod poller;
+mod proximity;
 mod read_measurements;
 mod register;
+mod retry;
+#[cfg(feature = "service")]
+mod service;
+#[cfg(feature = "shared")]
+mod shared;
 mod start_stop_measurements;
+#[cfg(feature = "stream")]
+mod stream;
+mod timestamp;
+#[cfg(feature = "float")]
+mod tracking;
+pub mod units;
+mod with_pins;
 
 /// VL6180 interface
 #[derive(Debug, Clone, Copy)]
@@ -99,13 +151,17 @@ pub struct VL6180X<MODE, I2C: I2c> {
 }
 
 /// Convenience container for VL6180, x_shutdown_pin and interrupt_pin
+///
+/// Offers the same mode transitions as [VL6180X] that need the `x_shutdown_pin`
+/// (powering off and on, hardware reset) driving its own pin, so the pin
+/// and the driver state cannot get out of sync.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub struct VL6180XwPins<MODE, I2C: I2c, OP: OutputPin, IP: InputPin> {
     /// VL6180
     pub vl6180x: VL6180X<MODE, I2C>,
     /// X Shutdown Pin, output high => powered on, output low => powered off.
-    /// Should call [VL6180X::power_off] and [VL6180X::power_on_and_init]
+    /// Should call [VL6180XwPins::power_off] and [VL6180XwPins::power_on_and_init]
     /// (Or the equivalent DynamicMode try methods) instead of
     /// manually setting the output of the pin.
     pub x_shutdown_pin: OP,
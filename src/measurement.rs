@@ -0,0 +1,63 @@
+use crate::{
+    register::RangeStatusErrorCode,
+    units::{MilliLux, Millimeters},
+};
+
+#[cfg(test)]
+mod measurement_tests;
+
+/// Result of a range measurement where nothing being in range is an ordinary value.
+///
+/// Range status errors that mean there is no target to measure are returned as
+/// [Range::NoTarget] or [Range::OutOfRange], the other range status errors are still
+/// returned as [Error::RangeStatusError](crate::Error::RangeStatusError).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Range {
+    /// Distance to the target.
+    Mm(Millimeters),
+    /// No target detected, or its return signal is too weak to be measured.
+    ///
+    /// Reported by the device as [RangeStatusErrorCode::MaxConvergence],
+    /// [RangeStatusErrorCode::EarlyConvergenceEstimate] or
+    /// [RangeStatusErrorCode::RangeIgnore].
+    NoTarget,
+    /// The target is beyond the maximum range of the current
+    /// [range scaling](crate::Config::set_range_result_scaler).
+    ///
+    /// Reported by the device as [RangeStatusErrorCode::RangingAlgoOverflow] or
+    /// [RangeStatusErrorCode::RawRangingAlgoOverflow].
+    OutOfRange,
+}
+
+/// A range or ambient light reading, for APIs that deliver both kinds in the order
+/// the device measures them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Sample {
+    /// An ambient light reading. In interleaved mode it is measured first in each cycle.
+    Ambient(MilliLux),
+    /// A range reading.
+    Range(Range),
+}
+
+impl Range {
+    /// Returns the distance if a target was measured.
+    pub fn mm(&self) -> Option<Millimeters> {
+        match self {
+            Range::Mm(mm) => Some(*mm),
+            _ => None,
+        }
+    }
+
+    /// The [Range] a range status error stands for,
+    /// or `None` if the error is not about the target being absent.
+    pub(crate) fn from_status_error(code: RangeStatusErrorCode) -> Option<Self> {
+        use RangeStatusErrorCode::*;
+        match code {
+            MaxConvergence | EarlyConvergenceEstimate | RangeIgnore => Some(Range::NoTarget),
+            RangingAlgoOverflow | RawRangingAlgoOverflow => Some(Range::OutOfRange),
+            _ => None,
+        }
+    }
+}
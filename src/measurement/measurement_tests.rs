@@ -0,0 +1,31 @@
+use super::*;
+
+#[test]
+fn range_from_status_error() {
+    use RangeStatusErrorCode::*;
+    for code in [MaxConvergence, EarlyConvergenceEstimate, RangeIgnore] {
+        assert_eq!(Range::from_status_error(code), Some(Range::NoTarget));
+    }
+    for code in [RangingAlgoOverflow, RawRangingAlgoOverflow] {
+        assert_eq!(Range::from_status_error(code), Some(Range::OutOfRange));
+    }
+    for code in [
+        VcselContinuityTest,
+        VcselWatchdogTest,
+        VcselWatchdog,
+        Pll1Lock,
+        Pll2Lock,
+        MaxSignalToNoiseRatio,
+        RawRangingAlgoUnderflow,
+        RangingAlgoUnderflow,
+    ] {
+        assert_eq!(Range::from_status_error(code), None);
+    }
+}
+
+#[test]
+fn range_mm() {
+    assert_eq!(Range::Mm(Millimeters(42)).mm(), Some(Millimeters(42)));
+    assert_eq!(Range::NoTarget.mm(), None);
+    assert_eq!(Range::OutOfRange.mm(), None);
+}
@@ -0,0 +1,309 @@
+//! Register-map backed bus and pin doubles used by the unit tests.
+extern crate std;
+
+use core::convert::Infallible;
+
+use embedded_hal::{
+    digital::{ErrorType, InputPin, OutputPin},
+    i2c::{ErrorKind, NoAcknowledgeSource, Operation},
+};
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+use std::{collections::VecDeque, vec::Vec};
+
+use crate::{Config, VL6180X};
+
+const REGISTER_COUNT: usize = 0x300;
+
+/// Simulates the sensor's register map.
+///
+/// Reads return the stored register contents and every written byte is
+/// stored and logged, so tests can both prepare results and inspect the
+/// commands the driver sent. Only answers to `device_address`, which follows
+/// writes to I2C_SLAVE__DEVICE_ADDRESS like the real device. Keeps track of the
+/// continuous measurements started and stopped with SYSRANGE__START and SYSALS__START,
+/// and completes single range measurements with the queued `range_results`.
+#[derive(Debug)]
+pub(crate) struct MockI2c {
+    pub(crate) registers: [u8; REGISTER_COUNT],
+    pub(crate) writes: Vec<(u16, u8)>,
+    pub(crate) device_address: u8,
+    /// Number of upcoming transactions that fail with a bus error.
+    pub(crate) bus_failures: u8,
+    /// Number of transactions addressed to the bus, including failed ones.
+    pub(crate) transactions: usize,
+    /// Register whose writes fail with a bus error.
+    pub(crate) failing_register: Option<u16>,
+    pub(crate) range_running: bool,
+    pub(crate) ambient_running: bool,
+    /// RESULT__RANGE_STATUS and RESULT__RANGE_VAL of the upcoming single range
+    /// measurements, each one is ready as soon as it is started.
+    pub(crate) range_results: VecDeque<(u8, u8)>,
+    /// Yield once before every transaction, so a test can drop the driver's future
+    /// between any two transactions.
+    pub(crate) yield_each_transaction: bool,
+}
+
+impl MockI2c {
+    pub(crate) fn new() -> Self {
+        let mut registers = [0; REGISTER_COUNT];
+        registers[0x000] = 0xB4; // IDENTIFICATION__MODEL_ID
+        registers[0x016] = 0x01; // SYSTEM__FRESH_OUT_OF_RESET
+        registers[0x04D] = 0x01; // RESULT__RANGE_STATUS, device ready
+        registers[0x04E] = 0x01; // RESULT__ALS_STATUS, device ready
+        Self {
+            registers,
+            writes: Vec::new(),
+            device_address: 0x29,
+            bus_failures: 0,
+            transactions: 0,
+            failing_register: None,
+            range_running: false,
+            ambient_running: false,
+            range_results: VecDeque::new(),
+            yield_each_transaction: false,
+        }
+    }
+
+    /// Brings the register map and measurements back to their state after a reset,
+    /// like holding the `x_shutdown_pin` low does. Keeps the write log.
+    pub(crate) fn reset(&mut self) {
+        let writes = core::mem::take(&mut self.writes);
+        let yield_each_transaction = self.yield_each_transaction;
+        *self = Self::new();
+        self.writes = writes;
+        self.yield_each_transaction = yield_each_transaction;
+    }
+
+    /// Sets a register as if the device had produced the value.
+    pub(crate) fn set(&mut self, reg: u16, value: u8) {
+        self.registers[reg as usize] = value;
+    }
+
+    /// Clears the RESULT__INTERRUPT_STATUS_GPIO bits like SYSTEM__INTERRUPT_CLEAR does.
+    fn clear_interrupts(&mut self, code: u8) {
+        let mut mask = 0;
+        if code & 0b001 != 0 {
+            mask |= 0b00_000_111;
+        }
+        if code & 0b010 != 0 {
+            mask |= 0b00_111_000;
+        }
+        if code & 0b100 != 0 {
+            mask |= 0b11_000_000;
+        }
+        self.registers[0x04F] &= !mask;
+    }
+
+    /// Makes a range sample of `raw` ready, without an error.
+    pub(crate) fn range_ready(&mut self, raw: u8) {
+        self.registers[0x04F] |= 0b00_000_100;
+        self.registers[0x04D] = 0x01;
+        self.registers[0x062] = raw;
+    }
+
+    /// Makes an ambient light sample with a raw count of 100 ready, without an error.
+    pub(crate) fn ambient_ready(&mut self) {
+        self.registers[0x04F] |= 0b00_100_000;
+        self.registers[0x04E] = 0x01;
+        self.registers[0x050] = 0;
+        self.registers[0x051] = 100;
+    }
+
+    /// Produces the next queued range result, if any.
+    fn complete_range_single(&mut self) {
+        if let Some((status, value)) = self.range_results.pop_front() {
+            self.registers[0x04D] = status;
+            self.registers[0x062] = value;
+            self.registers[0x04F] = self.registers[0x04F] & !0b111 | 0b100;
+        }
+    }
+
+    /// Returns true if `value` was ever written to `reg`.
+    pub(crate) fn was_written(&self, reg: u16, value: u8) -> bool {
+        self.writes.contains(&(reg, value))
+    }
+}
+
+impl embedded_hal::i2c::ErrorType for MockI2c {
+    type Error = ErrorKind;
+}
+
+impl I2c for MockI2c {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if self.yield_each_transaction {
+            embassy_futures::yield_now().await;
+        }
+        self.execute(address, operations)
+    }
+}
+
+/// The blocking bus, for the interrupt handler fast path.
+impl embedded_hal::i2c::I2c for MockI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
+
+impl MockI2c {
+    fn execute(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.transactions += 1;
+        if address != self.device_address {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        if self.bus_failures > 0 {
+            self.bus_failures -= 1;
+            return Err(ErrorKind::Bus);
+        }
+        let mut reg = 0_usize;
+        let mut is_write = false;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    reg = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+                    if bytes.len() > 2 && self.failing_register == Some(reg as u16) {
+                        return Err(ErrorKind::Bus);
+                    }
+                    for byte in &bytes[2..] {
+                        is_write = true;
+                        self.registers[reg] = *byte;
+                        self.writes.push((reg as u16, *byte));
+                        match reg {
+                            0x015 => self.clear_interrupts(*byte),
+                            0x018 if *byte == 0b11 => self.range_running ^= true,
+                            0x018 if *byte == 0b01 => self.complete_range_single(),
+                            0x038 if *byte == 0b11 => self.ambient_running ^= true,
+                            0x212 => self.device_address = *byte,
+                            _ => (),
+                        }
+                        reg += 1;
+                    }
+                }
+                Operation::Read(buffer) => {
+                    // Register writes are issued with a dummy read, which is ignored
+                    if is_write {
+                        continue;
+                    }
+                    for byte in buffer.iter_mut() {
+                        *byte = self.registers[reg];
+                        reg += 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pin double that remembers its state, every level it was set to and how often it
+/// was waited on. Waits return at once.
+#[derive(Debug, Default)]
+pub(crate) struct MockPin {
+    pub(crate) is_high: bool,
+    pub(crate) history: Vec<bool>,
+    pub(crate) waits: usize,
+}
+
+impl MockPin {
+    pub(crate) fn new(is_high: bool) -> Self {
+        Self {
+            is_high,
+            history: Vec::new(),
+            waits: 0,
+        }
+    }
+}
+
+impl ErrorType for MockPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.is_high = false;
+        self.history.push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.is_high = true;
+        self.history.push(true);
+        Ok(())
+    }
+}
+
+impl InputPin for MockPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_high)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_high)
+    }
+}
+
+impl Wait for MockPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.waits += 1;
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.waits += 1;
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits += 1;
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits += 1;
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.waits += 1;
+        Ok(())
+    }
+}
+
+/// Delay double that adds up the requested delays instead of waiting.
+#[derive(Debug, Default)]
+pub(crate) struct MockDelay {
+    pub(crate) total_ns: u64,
+}
+
+impl DelayNs for MockDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.total_ns += ns as u64;
+    }
+}
+
+/// Polls `future` at most `polls` times, then drops it. Returns whether it completed.
+pub(crate) fn poll_then_drop<F: core::future::Future>(future: F, polls: usize) -> bool {
+    let mut future = core::pin::pin!(future);
+    let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+    (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+}
+
+/// Builds a driver in the given mode without running the hardware init sequence.
+pub(crate) fn sensor<MODE>(mode: MODE) -> VL6180X<MODE, MockI2c> {
+    VL6180X {
+        mode,
+        com: MockI2c::new(),
+        config: Config::new(),
+    }
+}
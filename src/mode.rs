@@ -6,17 +6,25 @@ mod ready;
 pub use continuous::*;
 pub use dynamic::*;
 use embedded_hal::digital::OutputPin;
-use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
 pub use powered_off::*;
 pub use ready::*;
 
-use crate::{error::Error, VL6180X};
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{Mcps, MilliLux, Millimeters, Milliseconds, RawAmbientCount};
+use crate::{
+    config::RangeInterruptMode,
+    error::{Error, Error2},
+    register::{InterruptErrorCode, InterruptStatus},
+    Range, VL6180X,
+};
 
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
 where
     I2C: I2c<Error = E>,
 {
-    fn into_mode<MODE2>(self, mode: MODE2) -> VL6180X<MODE2, I2C> {
+    pub(crate) fn into_mode<MODE2>(self, mode: MODE2) -> VL6180X<MODE2, I2C> {
         VL6180X {
             mode,
             com: self.com,
@@ -39,6 +47,20 @@ pub trait AllowStartAmbientSingle {}
 /// range measurement
 pub trait AllowStartRangeSingle {}
 
+/// Operating modes with this trait can be recovered with a hardware reset,
+/// after which the sensor is returned to the same mode
+pub trait AllowRecovery {
+    /// The [OperatingMode] mirroring this mode
+    const OPERATING_MODE: OperatingMode;
+}
+
+/// Operating modes with this trait know the [OperatingMode] the sensor is in,
+/// also when it is only known at runtime
+pub trait CurrentOperatingMode {
+    /// The [OperatingMode] the sensor is in
+    fn current_operating_mode(&self) -> OperatingMode;
+}
+
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
 where
     I2C: I2c<Error = E>,
@@ -46,40 +68,81 @@ where
 {
     /// Blocking read of the range mesurement.
     /// The reading (whether single or continuous) must already have been started.
-    pub async fn read_range_mm_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn read_range_mm_blocking(&mut self) -> Result<Millimeters, Error<E>> {
         self.read_range_mm_blocking_direct().await
     }
 
     /// Non-blocking read of the range measurement.
     /// The reading (whether single or continuous) must already have been started.
     /// Returns [Error::ResultNotReady] if the result is not ready.
-    pub async fn read_range_mm(&mut self) -> Result<u16, Error<E>> {
+    pub async fn read_range_mm(&mut self) -> Result<Millimeters, Error<E>> {
         self.read_range_mm_direct().await
     }
 
+    /// Blocking read of the range measurement, returning no target
+    /// or out of range as a [Range] instead of an error.
+    /// The reading (whether single or continuous) must already have been started.
+    pub async fn read_range_blocking(&mut self) -> Result<Range, Error<E>> {
+        self.read_range_blocking_direct().await
+    }
+
+    /// Non-blocking read of the range measurement, returning no target
+    /// or out of range as a [Range] instead of an error.
+    /// The reading (whether single or continuous) must already have been started.
+    /// Returns [Error::ResultNotReady] if the result is not ready.
+    pub async fn read_range(&mut self) -> Result<Range, Error<E>> {
+        self.read_range_direct().await
+    }
+
+    /// Read the return signal rate of the latest range measurement, in MCPS
+    /// (mega counts per second) as 9.7 fixed point.
+    ///
+    /// A low return rate means a weak, noisier measurement. Read it after the range value
+    /// and before the next range measurement completes.
+    pub async fn read_range_return_rate(&mut self) -> Result<Mcps, Error<E>> {
+        self.read_range_return_rate_direct().await
+    }
+
     /// Blocking read of the ambient light mesurement.
     /// The reading (whether single or continuous) must already have been started.
-    pub async fn read_ambient_lux_blocking(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux_blocking(&mut self) -> Result<Lux, Error<E>> {
         self.read_ambient_lux_blocking_direct().await
     }
 
     /// Non-blocking read of the ambient light measurement.
     /// The reading (whether single or continuous) must already have been started.
     /// Returns [Error::ResultNotReady] if the result is not ready.
-    pub async fn read_ambient_lux(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux(&mut self) -> Result<Lux, Error<E>> {
         self.read_ambient_lux_direct().await
     }
 
+    /// Blocking read of the ambient light mesurement in milli-lux, converted with
+    /// integer maths only.
+    /// The reading (whether single or continuous) must already have been started.
+    pub async fn read_ambient_milli_lux_blocking(&mut self) -> Result<MilliLux, Error<E>> {
+        self.read_ambient_milli_lux_blocking_direct().await
+    }
+
+    /// Non-blocking read of the ambient light measurement in milli-lux, converted with
+    /// integer maths only.
+    /// The reading (whether single or continuous) must already have been started.
+    /// Returns [Error::ResultNotReady] if the result is not ready.
+    pub async fn read_ambient_milli_lux(&mut self) -> Result<MilliLux, Error<E>> {
+        self.read_ambient_milli_lux_direct().await
+    }
+
     /// Blocking read of the raw ambient light mesurement.
     /// The reading (whether single or continuous) must already have been started.
-    pub async fn read_ambient_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn read_ambient_blocking(&mut self) -> Result<RawAmbientCount, Error<E>> {
         self.read_ambient_blocking_direct().await
     }
 
     /// Non-blocking read of the raw ambient light measurement.
     /// The reading (whether single or continuous) must already have been started.
     /// Returns [Error::ResultNotReady] if the result is not ready.
-    pub async fn read_ambient(&mut self) -> Result<u16, Error<E>> {
+    pub async fn read_ambient(&mut self) -> Result<RawAmbientCount, Error<E>> {
         self.read_ambient_direct().await
     }
 }
@@ -124,6 +187,29 @@ where
     }
 }
 
+impl<MODE, I2C, E> VL6180X<MODE, I2C>
+where
+    I2C: I2c<Error = E>,
+    MODE: AllowRecovery,
+{
+    /// Recovers the sensor after a device or bus fault, see
+    /// [`Error::requires_hardware_reset()`].
+    ///
+    /// Resets the sensor by pulsing the `x_shutdown_pin` low, waits for it to boot,
+    /// initializes it with the stored config, restores the I2C address and then
+    /// restarts the measurements of the current mode.
+    pub async fn recover<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        self.hardware_reset_direct(x_shutdown_pin, delay).await?;
+        self.restart_operating_mode_direct(MODE::OPERATING_MODE)
+            .await?;
+        Ok(())
+    }
+}
+
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
 where
     I2C: I2c<Error = E>,
@@ -135,12 +221,21 @@ where
     }
 
     /// Read the current interrupt status of the sensor.
-    /// Can be in multiple states of [ResultInterruptStatusGpioCode](crate::register::ResultInterruptStatusGpioCode) at once.
-    /// Use [ResultInterruptStatusGpioCode::has_status](crate::register::ResultInterruptStatusGpioCode::has_status) to look for particular states.
-    pub async fn read_interrupt_status(&mut self) -> Result<u8, Error<E>> {
+    /// Reports range, ambient and error events at once, see [InterruptStatus].
+    pub async fn read_interrupt_status(&mut self) -> Result<InterruptStatus, Error<E>> {
         self.read_interrupt_status_direct().await
     }
 
+    /// Read the device errors reported by the interrupt status and clear them.
+    ///
+    /// Measurement reads return [Error::LaserSafetyError] or [Error::PllError] until
+    /// the error interrupt is cleared, either with this or
+    /// [`clear_error_interrupt()`](VL6180X::clear_error_interrupt). An unknown error
+    /// code is cleared as well before [Error::UnknownRegisterCode] is returned.
+    pub async fn read_device_errors(&mut self) -> Result<InterruptErrorCode, Error<E>> {
+        self.read_device_errors_direct().await
+    }
+
     /// Clear error interrupt
     pub async fn clear_error_interrupt(&mut self) -> Result<(), Error<E>> {
         self.clear_error_interrupt_direct().await
@@ -170,6 +265,18 @@ where
         Ok(self.into_mode(PoweredOffMode {}))
     }
 
+    /// Resets the sensor by pulsing the `x_shutdown_pin` low.
+    /// It then busy waits for the device to be booted and initializes the device
+    /// with the stored config.
+    pub async fn hardware_reset<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<VL6180X<ReadyMode, I2C>, Error2<E, PE>> {
+        self.hardware_reset_direct(x_shutdown_pin, delay).await?;
+        Ok(self.into_mode(ReadyMode))
+    }
+
     /// Change current i2c address to new i2c address.
     ///
     /// After completion the device will answer to the new address programmed.
@@ -181,4 +288,74 @@ where
     pub async fn change_i2c_address(&mut self, new_address: u8) -> Result<(), Error<E>> {
         self.change_i2c_address_direct(new_address).await
     }
+
+    /// Change the ambient light analogue gain level while the device is running,
+    /// see [`Config::set_ambient_analogue_gain_level()`](crate::Config::set_ambient_analogue_gain_level).
+    ///
+    /// A measurement that was already running when the gain changed still uses the
+    /// old gain, so the next result of a continuous mode should be discarded.
+    ///
+    /// Level Min = 0; Max = 7
+    pub async fn set_ambient_analogue_gain_level(&mut self, level: u8) -> Result<(), Error<E>>
+    where
+        MODE: CurrentOperatingMode,
+    {
+        let integration_period = self.config.ambient_integration_period;
+        self.set_ambient_gain_and_integration_direct(level, integration_period)
+            .await
+    }
+
+    /// Change the ambient light integration period while the device is running,
+    /// see [`Config::set_ambient_integration_period()`](crate::Config::set_ambient_integration_period).
+    ///
+    /// A measurement that was already running when the period changed still uses the
+    /// old period, so the next result of a continuous mode should be discarded.
+    ///
+    /// Min = 1ms; Max = 256ms, and
+    /// `time` * 1.1 ≤ [ambient_inter_measurement_period](crate::Config::set_ambient_inter_measurement_period) * 0.9,
+    /// in interleaved mode also
+    /// ([range_max_convergence_time](crate::Config::set_range_max_convergence_time) + 5)
+    /// + `time` * 1.1 ≤ ambient_inter_measurement_period * 0.9
+    pub async fn set_ambient_integration_period(
+        &mut self,
+        time: Milliseconds,
+    ) -> Result<(), Error<E>>
+    where
+        MODE: CurrentOperatingMode,
+    {
+        let gain_level = self.config.ambient_analogue_gain_level;
+        self.set_ambient_gain_and_integration_direct(gain_level, time.0)
+            .await
+    }
+
+    /// Change the range scaling factor while the device is running,
+    /// see [`Config::set_range_result_scaler()`](crate::Config::set_range_result_scaler).
+    ///
+    /// Also scales the part-to-part offset and crosstalk valid height, and enables the
+    /// early convergence estimate only at 1x, like the initialization does. The range
+    /// interrupt thresholds are rescaled to the nearest value for the same distance,
+    /// except a high threshold of 255.
+    /// A measurement that was already running when the scaling changed still uses the
+    /// old scaling, so the next result of a continuous mode should be discarded.
+    ///
+    /// Min = 1x; Max = 3x
+    pub async fn set_range_result_scaler(&mut self, scaler: u8) -> Result<(), Error<E>> {
+        self.set_range_result_scaler_direct(scaler).await
+    }
+
+    /// Change the range interrupt mode and thresholds while the device is running,
+    /// see [`Config::set_range_interrupt_mode()`](crate::Config::set_range_interrupt_mode).
+    ///
+    /// The thresholds are multiplied by the range scaler, like the ones in the config,
+    /// which is updated with the new values.
+    pub async fn set_range_interrupt(
+        &mut self,
+        interrupt_mode: RangeInterruptMode,
+        low_threshold: u8,
+        high_threshold: u8,
+    ) -> Result<(), Error<E>> {
+        self.set_range_interrupt_direct(interrupt_mode, low_threshold, high_threshold)
+            .await?;
+        Ok(())
+    }
 }
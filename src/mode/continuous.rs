@@ -1,10 +1,14 @@
 use embedded_hal_async::i2c::I2c;
 
 use super::{
-    AllowReadMeasurement, AllowStartAmbientSingle, AllowStartRangeSingle, ReadyMode,
+    AllowReadMeasurement, AllowRecovery, AllowStartAmbientSingle, AllowStartRangeSingle,
+    CurrentOperatingMode, OperatingMode, ReadyMode,
 };
 use crate::{error::Error, AllowCommunication, VL6180X};
 
+#[cfg(test)]
+mod continuous_tests;
+
 /// Mode in which continuous range measurements are being taken by the sensor
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
@@ -16,6 +20,16 @@ impl AllowStartAmbientSingle for RangeContinuousMode {}
 
 impl AllowCommunication for RangeContinuousMode {}
 
+impl AllowRecovery for RangeContinuousMode {
+    const OPERATING_MODE: OperatingMode = OperatingMode::RangeContinuous;
+}
+
+impl CurrentOperatingMode for RangeContinuousMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        OperatingMode::RangeContinuous
+    }
+}
+
 impl<I2C, E> VL6180X<RangeContinuousMode, I2C>
 where
     I2C: I2c<Error = E>,
@@ -27,6 +41,34 @@ where
         self.toggle_range_continuous_direct().await?;
         Ok(self.into_mode(ReadyMode {}))
     }
+
+    /// Switches directly from range continuous mode to ambient continuous mode.
+    ///
+    /// Stops the range measurements and waits for the one in progress to finish
+    /// before starting the ambient measurements.
+    pub async fn switch_to_ambient_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<AmbientContinuousMode, I2C>, Error<E>> {
+        self.stop_range_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(AmbientContinuousMode {});
+        new_vl6180x.toggle_ambient_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
+
+    /// Switches directly from range continuous mode to interleaved continuous mode.
+    ///
+    /// The interleaved configuration is checked before anything is stopped.
+    /// Stops the range measurements and waits for the one in progress to finish
+    /// before starting the interleaved measurements.
+    pub async fn switch_to_interleaved_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<InterleavedContinuousMode, I2C>, Error<E>> {
+        self.check_config_valid()?;
+        self.stop_range_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(InterleavedContinuousMode {});
+        new_vl6180x.enable_interleaved_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
 }
 
 /// Mode in which continuous ambient light measurements are being taken by the sensor
@@ -40,6 +82,16 @@ impl AllowStartRangeSingle for AmbientContinuousMode {}
 
 impl AllowCommunication for AmbientContinuousMode {}
 
+impl AllowRecovery for AmbientContinuousMode {
+    const OPERATING_MODE: OperatingMode = OperatingMode::AmbientContinuous;
+}
+
+impl CurrentOperatingMode for AmbientContinuousMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        OperatingMode::AmbientContinuous
+    }
+}
+
 impl<I2C, E> VL6180X<AmbientContinuousMode, I2C>
 where
     I2C: I2c<Error = E>,
@@ -51,6 +103,34 @@ where
         self.toggle_ambient_continuous_direct().await?;
         Ok(self.into_mode(ReadyMode {}))
     }
+
+    /// Switches directly from ambient continuous mode to range continuous mode.
+    ///
+    /// Stops the ambient measurements and waits for the one in progress to finish
+    /// before starting the range measurements.
+    pub async fn switch_to_range_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<RangeContinuousMode, I2C>, Error<E>> {
+        self.stop_ambient_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(RangeContinuousMode {});
+        new_vl6180x.toggle_range_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
+
+    /// Switches directly from ambient continuous mode to interleaved continuous mode.
+    ///
+    /// The interleaved configuration is checked before anything is stopped.
+    /// Stops the ambient measurements and waits for the one in progress to finish
+    /// before starting the interleaved measurements.
+    pub async fn switch_to_interleaved_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<InterleavedContinuousMode, I2C>, Error<E>> {
+        self.check_config_valid()?;
+        self.stop_ambient_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(InterleavedContinuousMode {});
+        new_vl6180x.enable_interleaved_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
 }
 
 /// Mode in which continuous ambient and range measurements are being taken by the sensor.
@@ -65,6 +145,16 @@ impl AllowReadMeasurement for InterleavedContinuousMode {}
 
 impl AllowCommunication for InterleavedContinuousMode {}
 
+impl AllowRecovery for InterleavedContinuousMode {
+    const OPERATING_MODE: OperatingMode = OperatingMode::InterleavedContinuous;
+}
+
+impl CurrentOperatingMode for InterleavedContinuousMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        OperatingMode::InterleavedContinuous
+    }
+}
+
 impl<I2C, E> VL6180X<InterleavedContinuousMode, I2C>
 where
     I2C: I2c<Error = E>,
@@ -76,4 +166,30 @@ where
         self.stop_interleaved_continuous_direct().await?;
         Ok(self.into_mode(ReadyMode {}))
     }
+
+    /// Switches directly from interleaved continuous mode to range continuous mode.
+    ///
+    /// Stops the interleaved measurements and waits for the ones in progress to finish
+    /// before starting the range measurements.
+    pub async fn switch_to_range_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<RangeContinuousMode, I2C>, Error<E>> {
+        self.stop_interleaved_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(RangeContinuousMode {});
+        new_vl6180x.toggle_range_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
+
+    /// Switches directly from interleaved continuous mode to ambient continuous mode.
+    ///
+    /// Stops the interleaved measurements and waits for the ones in progress to finish
+    /// before starting the ambient measurements.
+    pub async fn switch_to_ambient_continuous_mode(
+        mut self,
+    ) -> Result<VL6180X<AmbientContinuousMode, I2C>, Error<E>> {
+        self.stop_interleaved_continuous_and_wait_direct().await?;
+        let mut new_vl6180x = self.into_mode(AmbientContinuousMode {});
+        new_vl6180x.toggle_ambient_continuous_direct().await?;
+        Ok(new_vl6180x)
+    }
 }
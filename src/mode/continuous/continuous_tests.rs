@@ -0,0 +1,63 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::mock::{self, MockDelay, MockPin};
+
+#[test]
+fn range_to_ambient_stops_range_then_starts_ambient() {
+    let sensor = mock::sensor(RangeContinuousMode);
+    let sensor = block_on(sensor.switch_to_ambient_continuous_mode()).unwrap();
+    assert_eq!(sensor.com.writes, [(0x018, 0b11), (0x038, 0b11)]);
+}
+
+#[test]
+fn ambient_to_range_stops_ambient_then_starts_range() {
+    let sensor = mock::sensor(AmbientContinuousMode);
+    let sensor = block_on(sensor.switch_to_range_continuous_mode()).unwrap();
+    assert_eq!(sensor.com.writes, [(0x038, 0b11), (0x018, 0b11)]);
+}
+
+#[test]
+fn range_to_interleaved_enables_interleaved() {
+    let sensor = mock::sensor(RangeContinuousMode);
+    let sensor = block_on(sensor.switch_to_interleaved_continuous_mode()).unwrap();
+    assert_eq!(
+        sensor.com.writes,
+        [(0x018, 0b11), (0x2A3, 1), (0x038, 0b11)]
+    );
+}
+
+#[test]
+fn interleaved_to_range_stops_ambient_and_disables_interleaved() {
+    let sensor = mock::sensor(InterleavedContinuousMode {});
+    let sensor = block_on(sensor.switch_to_range_continuous_mode()).unwrap();
+    assert_eq!(
+        sensor.com.writes,
+        [(0x038, 0b11), (0x2A3, 0), (0x018, 0b11)]
+    );
+}
+
+#[test]
+fn switch_to_interleaved_checks_config_before_stopping() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    sensor.config.ambient_inter_measurement_period = 10;
+    let result = block_on(sensor.switch_to_interleaved_continuous_mode());
+    assert_eq!(result.err(), Some(Error::InvalidConfigurationValue(10)));
+}
+
+#[test]
+fn switch_times_out_if_measurement_never_finishes() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    sensor.com.set(0x04D, 0x00);
+    let result = block_on(sensor.switch_to_ambient_continuous_mode());
+    assert_eq!(result.err(), Some(Error::Timeout));
+}
+
+#[test]
+fn recover_restarts_typed_mode() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    let mut pin = MockPin::new(true);
+    block_on(sensor.recover(&mut pin, &mut MockDelay::default())).unwrap();
+    assert_eq!(pin.history, [false, true]);
+    assert_eq!(sensor.com.writes.last(), Some(&(0x038, 0b11)));
+}
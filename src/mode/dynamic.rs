@@ -1,20 +1,55 @@
 use embedded_hal::digital::OutputPin;
-use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
 use OperatingMode::*;
 
+use super::{
+    AmbientContinuousMode, CurrentOperatingMode, InterleavedContinuousMode, PoweredOffMode,
+    RangeContinuousMode, ReadyMode,
+};
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{Mcps, MilliLux, Millimeters, Milliseconds, RawAmbientCount};
 use crate::{
+    config::{Config, RangeInterruptMode},
+    device_status::HARDWARE_RESET_PULSE_US,
     error::{Error, Error2},
-    VL6180X,
+    register::{
+        InterleavedModeEnableCode, InterruptErrorCode,
+        Register8Bit::{INTERLEAVED_MODE__ENABLE, RESULT__ALS_STATUS, RESULT__RANGE_STATUS},
+    },
+    Range, VL6180X,
 };
 
+#[cfg(test)]
+mod dynamic_tests;
+
 /// A mode where the state is kept track of at runtime, instead of being
 /// encoded into the type. Thus allowing you to change the mode often,
 /// and without problems with ownership, or references, at the cost of some
 /// performance and the risk of runtime errors.
+///
+/// # Cancellation
+///
+/// Mode transitions write several registers. The driver keeps track of every write
+/// that completed, so a transition that is interrupted, by an error or by dropping
+/// its future, leaves the [OperatingMode] as it was before the call and the next
+/// call first brings the device back to it. A transition is also carried on from
+/// wherever an interrupted one left the device, e.g. a dropped
+/// [`try_power_on_and_init()`](VL6180X::try_power_on_and_init) can simply be called
+/// again.
+///
+/// A dropped [`hardware_reset()`](VL6180X::hardware_reset) or
+/// [`recover()`](VL6180X::recover) may leave the device held in reset, then the
+/// other methods return [Error::InvalidMethod] with [PoweredOff] until
+/// [`recover()`](VL6180X::recover) completes.
+///
+/// The driver assumes an I2C transaction either completes or has no effect when its
+/// future is dropped.
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub struct DynamicMode {
     operating_mode: OperatingMode,
+    device: DeviceState,
 }
 
 /// Sensor operating modes that the driver uses to determine
@@ -22,7 +57,7 @@ pub struct DynamicMode {
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub enum OperatingMode {
-    /// Mirrors [PoweredOffMode](crate::mode::PoweredOffMode)
+    /// Mirrors [PoweredOffMode]
     PoweredOff,
     /// Mirrors [Ready](crate::mode::ReadyMode)
     Ready,
@@ -34,10 +69,51 @@ pub enum OperatingMode {
     InterleavedContinuous,
 }
 
+/// What the device is doing according to the register writes that completed.
+///
+/// Only differs from the [OperatingMode] while a transition is in progress or after
+/// one was interrupted.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+enum DeviceState {
+    /// Held in reset by the `x_shutdown_pin`.
+    Off,
+    /// Powered on, the initialization did not complete. Keeps the I2C address to
+    /// set once it does.
+    Booting { address: u8 },
+    /// Initialized and not measuring.
+    Idle,
+    /// The continuous measurements of the mode are running.
+    Running(OperatingMode),
+    /// The continuous measurements of the mode were stopped, the measurement in
+    /// progress may not have finished yet.
+    Stopping(OperatingMode),
+    /// INTERLEAVED_MODE__ENABLE is set while not measuring.
+    InterleavedEnabled,
+}
+
+impl DeviceState {
+    /// The state of a device that is settled in `operating_mode`.
+    fn settled(operating_mode: OperatingMode) -> Self {
+        match operating_mode {
+            PoweredOff => DeviceState::Off,
+            Ready => DeviceState::Idle,
+            operating_mode => DeviceState::Running(operating_mode),
+        }
+    }
+}
+
+impl CurrentOperatingMode for DynamicMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        self.operating_mode
+    }
+}
+
 impl DynamicMode {
     pub(crate) fn new() -> Self {
         Self {
             operating_mode: Ready,
+            device: DeviceState::Idle,
         }
     }
 }
@@ -46,26 +122,240 @@ impl<I2C, E> VL6180X<DynamicMode, I2C>
 where
     I2C: I2c<Error = E>,
 {
+    /// Brings the device to `target` one register write at a time, keeping track of
+    /// each one, so this carries on from wherever an interrupted call left the device.
+    ///
+    /// Does not check the config of the interleaved mode.
+    async fn drive_device(&mut self, target: OperatingMode) -> Result<(), Error<E>> {
+        use DeviceState::*;
+        loop {
+            self.mode.device = match (self.mode.device, target) {
+                (Off, _) | (Booting { .. }, _) => {
+                    return Err(Error::InvalidMethod(PoweredOff))
+                }
+                (Running(mode), target) if mode == target => return Ok(()),
+                (Idle, RangeContinuous) => {
+                    self.toggle_range_continuous_direct().await?;
+                    Running(RangeContinuous)
+                }
+                (Idle, AmbientContinuous) => {
+                    self.toggle_ambient_continuous_direct().await?;
+                    Running(AmbientContinuous)
+                }
+                (Idle, InterleavedContinuous) => {
+                    self.write_named_register(
+                        INTERLEAVED_MODE__ENABLE,
+                        InterleavedModeEnableCode::Enable as u8,
+                    )
+                    .await?;
+                    InterleavedEnabled
+                }
+                (Idle, _) => return Ok(()),
+                (InterleavedEnabled, InterleavedContinuous) => {
+                    // Interleaved measurements are driven by the ambient continuous mode
+                    self.toggle_ambient_continuous_direct().await?;
+                    Running(InterleavedContinuous)
+                }
+                (InterleavedEnabled, _) => {
+                    self.stop_interleaved_continuous_direct().await?;
+                    Idle
+                }
+                (Running(RangeContinuous), _) => {
+                    self.toggle_range_continuous_direct().await?;
+                    Stopping(RangeContinuous)
+                }
+                (Running(mode), _) => {
+                    self.toggle_ambient_continuous_direct().await?;
+                    Stopping(mode)
+                }
+                (Stopping(RangeContinuous), _) => {
+                    self.wait_device_ready(RESULT__RANGE_STATUS).await?;
+                    Idle
+                }
+                (Stopping(AmbientContinuous), _) => {
+                    self.wait_device_ready(RESULT__ALS_STATUS).await?;
+                    Idle
+                }
+                (Stopping(_), _) => {
+                    self.wait_device_ready(RESULT__ALS_STATUS).await?;
+                    self.wait_device_ready(RESULT__RANGE_STATUS).await?;
+                    InterleavedEnabled
+                }
+            };
+        }
+    }
+
+    /// Brings the device back to the [OperatingMode] after an interrupted transition.
+    /// Does nothing if they match.
+    async fn repair(&mut self) -> Result<(), Error<E>> {
+        self.drive_device(self.mode.operating_mode).await
+    }
+
+    /// Switches the device to `target` and the OperatingMode along with it once the
+    /// device is there.
+    async fn transition(&mut self, target: OperatingMode) -> Result<(), Error<E>> {
+        self.drive_device(target).await?;
+        self.mode.operating_mode = target;
+        Ok(())
+    }
+
+    /// Powers on and initializes the device, carrying on with an initialization that
+    /// was interrupted.
+    async fn power_on_device<PE, P: OutputPin<Error = PE>>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+    ) -> Result<(), Error2<E, PE>> {
+        let (address, resume) = match self.mode.device {
+            DeviceState::Booting { address } => (address, true),
+            _ => (self.config.address, false),
+        };
+        x_shutdown_pin
+            .set_high()
+            .map_err(|e| Error2::GpioPinError(e))?;
+        self.mode.device = DeviceState::Booting { address };
+        self.init_powered_on_direct(address, resume).await?;
+        self.mode.device = DeviceState::Idle;
+        Ok(())
+    }
+
+    /// Holds the device in reset, then powers it on and initializes it.
+    async fn reset_device<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        x_shutdown_pin
+            .set_low()
+            .map_err(|e| Error2::GpioPinError(e))?;
+        self.device_off();
+        delay.delay_us(HARDWARE_RESET_PULSE_US).await;
+        self.power_on_device(x_shutdown_pin).await
+    }
+
+    /// Keeps track of the `x_shutdown_pin` having been set low.
+    fn device_off(&mut self) {
+        if let DeviceState::Booting { address } = self.mode.device {
+            // The interrupted initialization left the default address in the config
+            self.config.address = address;
+        }
+        self.mode.device = DeviceState::Off;
+    }
+}
+
+impl<I2C, E> VL6180X<DynamicMode, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// The [OperatingMode] the sensor is currently in.
+    pub fn operating_mode(&self) -> OperatingMode {
+        self.mode.operating_mode
+    }
+
+    /// Convert back into [ReadyMode] to regain the compile time
+    /// guarantees of the typed modes.
+    /// Returns the unchanged driver if the OperatingMode is not [Ready].
+    pub fn try_into_ready_mode(self) -> Result<VL6180X<ReadyMode, I2C>, Self> {
+        if self.mode.operating_mode != Ready {
+            return Err(self);
+        }
+        Ok(self.into_mode(ReadyMode))
+    }
+
+    /// Convert back into [RangeContinuousMode] to regain
+    /// the compile time guarantees of the typed modes.
+    /// Returns the unchanged driver if the OperatingMode is not [RangeContinuous].
+    pub fn try_into_range_continuous_mode(
+        self,
+    ) -> Result<VL6180X<RangeContinuousMode, I2C>, Self> {
+        if self.mode.operating_mode != RangeContinuous {
+            return Err(self);
+        }
+        Ok(self.into_mode(RangeContinuousMode))
+    }
+
+    /// Convert back into [AmbientContinuousMode] to
+    /// regain the compile time guarantees of the typed modes.
+    /// Returns the unchanged driver if the OperatingMode is not [AmbientContinuous].
+    pub fn try_into_ambient_continuous_mode(
+        self,
+    ) -> Result<VL6180X<AmbientContinuousMode, I2C>, Self> {
+        if self.mode.operating_mode != AmbientContinuous {
+            return Err(self);
+        }
+        Ok(self.into_mode(AmbientContinuousMode))
+    }
+
+    /// Convert back into [InterleavedContinuousMode]
+    /// to regain the compile time guarantees of the typed modes.
+    /// Returns the unchanged driver if the OperatingMode is not [InterleavedContinuous].
+    pub fn try_into_interleaved_continuous_mode(
+        self,
+    ) -> Result<VL6180X<InterleavedContinuousMode, I2C>, Self> {
+        if self.mode.operating_mode != InterleavedContinuous {
+            return Err(self);
+        }
+        Ok(self.into_mode(InterleavedContinuousMode {}))
+    }
+
+    /// Convert back into [PoweredOffMode] to regain the
+    /// compile time guarantees of the typed modes.
+    /// Returns the unchanged driver if the OperatingMode is not [PoweredOff].
+    pub fn try_into_powered_off_mode(self) -> Result<VL6180X<PoweredOffMode, I2C>, Self> {
+        if self.mode.operating_mode != PoweredOff {
+            return Err(self);
+        }
+        Ok(self.into_mode(PoweredOffMode {}))
+    }
+
     /// Same functionality as [`poll_range_mm_single_blocking()`](VL6180X::poll_range_mm_single_blocking)
     /// but with a check on the current [OperatingMode].
     /// Valid when OperatingMode is [Ready], otherwise returns [Error::InvalidMethod]
-    pub async fn try_poll_range_mm_single_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn try_poll_range_mm_single_blocking(
+        &mut self,
+    ) -> Result<Millimeters, Error<E>> {
         if self.mode.operating_mode != Ready {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.poll_range_mm_single_blocking_direct().await
     }
 
+    /// Same functionality as [`poll_range_single_blocking()`](VL6180X::poll_range_single_blocking)
+    /// but with a check on the current [OperatingMode].
+    /// Valid when OperatingMode is [Ready], otherwise returns [Error::InvalidMethod]
+    pub async fn try_poll_range_single_blocking(&mut self) -> Result<Range, Error<E>> {
+        if self.mode.operating_mode != Ready {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.poll_range_single_blocking_direct().await
+    }
+
     /// Same functionality as [`poll_ambient_lux_single_blocking()`](VL6180X::poll_ambient_lux_single_blocking)
     /// but with a check on the current [OperatingMode].
     /// Valid when OperatingMode is [Ready], otherwise returns [Error::InvalidMethod]
-    pub async fn try_poll_ambient_lux_single_blocking(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn try_poll_ambient_lux_single_blocking(&mut self) -> Result<Lux, Error<E>> {
         if self.mode.operating_mode != Ready {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.poll_ambient_lux_single_blocking_direct().await
     }
 
+    /// Same functionality as [`poll_ambient_milli_lux_single_blocking()`](VL6180X::poll_ambient_milli_lux_single_blocking)
+    /// but with a check on the current [OperatingMode].
+    /// Valid when OperatingMode is [Ready], otherwise returns [Error::InvalidMethod]
+    pub async fn try_poll_ambient_milli_lux_single_blocking(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        if self.mode.operating_mode != Ready {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.poll_ambient_milli_lux_single_blocking_direct().await
+    }
+
     /// Same functionality as [`start_range_continuous_mode()`](VL6180X::start_range_continuous_mode)
     /// but with a check on the current [OperatingMode].
     /// Valid when OperatingMode is [Ready], otherwise returns [Error::InvalidMethod]
@@ -73,9 +363,7 @@ where
         if self.mode.operating_mode != Ready {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.toggle_range_continuous_direct().await?;
-        self.mode.operating_mode = RangeContinuous;
-        Ok(())
+        self.transition(RangeContinuous).await
     }
 
     /// Same functionality as [`stop_range_continuous_mode()`](VL6180X::stop_range_continuous_mode)
@@ -85,9 +373,7 @@ where
         if self.mode.operating_mode != RangeContinuous {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.toggle_range_continuous_direct().await?;
-        self.mode.operating_mode = Ready;
-        Ok(())
+        self.transition(Ready).await
     }
 
     /// Same functionality as [`start_ambient_continuous_mode()`](VL6180X::start_ambient_continuous_mode)
@@ -97,9 +383,7 @@ where
         if self.mode.operating_mode != Ready {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.toggle_ambient_continuous_direct().await?;
-        self.mode.operating_mode = AmbientContinuous;
-        Ok(())
+        self.transition(AmbientContinuous).await
     }
 
     /// Same functionality as [`stop_ambient_continuous_mode()`](VL6180X::stop_ambient_continuous_mode)
@@ -109,9 +393,7 @@ where
         if self.mode.operating_mode != AmbientContinuous {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.toggle_ambient_continuous_direct().await?;
-        self.mode.operating_mode = Ready;
-        Ok(())
+        self.transition(Ready).await
     }
 
     /// Same functionality as [`start_interleaved_continuous_mode()`](VL6180X::start_interleaved_continuous_mode)
@@ -121,9 +403,8 @@ where
         if self.mode.operating_mode != Ready {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.enable_interleaved_continuous_direct().await?;
-        self.mode.operating_mode = InterleavedContinuous;
-        Ok(())
+        self.check_config_valid()?;
+        self.transition(InterleavedContinuous).await
     }
 
     /// Same functionality as [`stop_interleaved_continuous_mode()`](VL6180X::stop_interleaved_continuous_mode)
@@ -133,35 +414,73 @@ where
         if self.mode.operating_mode != InterleavedContinuous {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
-        self.stop_interleaved_continuous_direct().await?;
-        self.mode.operating_mode = Ready;
-        Ok(())
+        self.transition(Ready).await
+    }
+
+    /// Switches the sensor directly to the `target` [OperatingMode].
+    ///
+    /// Stops the current continuous mode (if any) and waits for the measurement in
+    /// progress to finish before starting the target mode, so there is no need to go
+    /// through [Ready] manually. If the sensor is already in `target` this only finishes
+    /// an interrupted transition, if any.
+    ///
+    /// Switching to or from [PoweredOff] needs the `x_shutdown_pin`, use
+    /// [`try_power_off()`](VL6180X::try_power_off) and
+    /// [`try_power_on_and_init()`](VL6180X::try_power_on_and_init) instead.
+    /// Returns [Error::InvalidMethod] in that case.
+    pub async fn set_operating_mode(
+        &mut self,
+        target: OperatingMode,
+    ) -> Result<(), Error<E>> {
+        let current = self.mode.operating_mode;
+        if current == PoweredOff && target == PoweredOff {
+            return Ok(());
+        }
+        if current == PoweredOff || target == PoweredOff {
+            return Err(Error::InvalidMethod(current));
+        }
+        if target == InterleavedContinuous && current != target {
+            self.check_config_valid()?;
+        }
+        self.transition(target).await
     }
 
     /// Same functionality as [`start_range_single()`](VL6180X::start_range_single)
     /// but with a check on the current [OperatingMode].
     /// Valid when OperatingMode is [Ready] or [AmbientContinuous],
     /// otherwise returns [Error::InvalidMethod]
-    pub async fn try_start_range_single(&mut self) -> Result<(), E> {
-        self.start_range_single_direct().await
+    pub async fn try_start_range_single(&mut self) -> Result<(), Error<E>> {
+        if self.mode.operating_mode != Ready && self.mode.operating_mode != AmbientContinuous
+        {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.start_range_single_direct().await?;
+        Ok(())
     }
 
     /// Same functionality as [`start_ambient_single()`](VL6180X::start_ambient_single)
     /// but with a check on the current [OperatingMode].
     /// Valid when OperatingMode is [Ready] or [RangeContinuous],
     /// otherwise returns [Error::InvalidMethod]
-    pub async fn try_start_ambient_single(&mut self) -> Result<(), E> {
-        self.start_ambient_single_direct().await
+    pub async fn try_start_ambient_single(&mut self) -> Result<(), Error<E>> {
+        if self.mode.operating_mode != Ready && self.mode.operating_mode != RangeContinuous {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.start_ambient_single_direct().await?;
+        Ok(())
     }
 
     /// Same functionality as [`read_range_mm_blocking()`](VL6180X::read_range_mm_blocking)
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_range_mm_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn try_read_range_mm_blocking(&mut self) -> Result<Millimeters, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_range_mm_blocking_direct().await
     }
 
@@ -169,21 +488,48 @@ where
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_range_mm(&mut self) -> Result<u16, Error<E>> {
+    pub async fn try_read_range_mm(&mut self) -> Result<Millimeters, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_range_mm_direct().await
     }
 
+    /// Same functionality as [`read_range_blocking()`](VL6180X::read_range_blocking)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_range_blocking(&mut self) -> Result<Range, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_range_blocking_direct().await
+    }
+
+    /// Same functionality as [`read_range()`](VL6180X::read_range)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_range(&mut self) -> Result<Range, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_range_direct().await
+    }
+
     /// Same functionality as [`read_ambient_lux_blocking()`](VL6180X::read_ambient_lux_blocking)
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_ambient_lux_blocking(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux_blocking(&mut self) -> Result<Lux, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_ambient_lux_blocking_direct().await
     }
 
@@ -191,21 +537,50 @@ where
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_ambient_lux(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux(&mut self) -> Result<Lux, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_ambient_lux_direct().await
     }
 
+    /// Same functionality as [`read_ambient_milli_lux_blocking()`](VL6180X::read_ambient_milli_lux_blocking)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_ambient_milli_lux_blocking(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_ambient_milli_lux_blocking_direct().await
+    }
+
+    /// Same functionality as [`read_ambient_milli_lux()`](VL6180X::read_ambient_milli_lux)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_ambient_milli_lux(&mut self) -> Result<MilliLux, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_ambient_milli_lux_direct().await
+    }
+
     /// Same functionality as [`read_ambient_blocking()`](VL6180X::read_ambient_blocking)
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_ambient_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn try_read_ambient_blocking(&mut self) -> Result<RawAmbientCount, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_ambient_blocking_direct().await
     }
 
@@ -213,13 +588,38 @@ where
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
     /// in which case will return [Error::InvalidMethod]
-    pub async fn try_read_ambient(&mut self) -> Result<u16, Error<E>> {
+    pub async fn try_read_ambient(&mut self) -> Result<RawAmbientCount, Error<E>> {
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.read_ambient_direct().await
     }
 
+    /// Same functionality as [`read_range_return_rate()`](VL6180X::read_range_return_rate)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_range_return_rate(&mut self) -> Result<Mcps, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_range_return_rate_direct().await
+    }
+
+    /// Same functionality as [`read_device_errors()`](VL6180X::read_device_errors)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_read_device_errors(&mut self) -> Result<InterruptErrorCode, Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.read_device_errors_direct().await
+    }
+
     /// Same functionality as [`clear_error_interrupt()`](VL6180X::clear_error_interrupt)
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
@@ -228,6 +628,7 @@ where
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.clear_error_interrupt_direct().await
     }
 
@@ -239,6 +640,7 @@ where
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.clear_ambient_interrupt_direct().await
     }
 
@@ -250,6 +652,7 @@ where
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.clear_range_interrupt_direct().await
     }
 
@@ -261,6 +664,7 @@ where
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.clear_all_interrupts_direct().await
     }
 
@@ -272,9 +676,88 @@ where
         if self.mode.operating_mode == PoweredOff {
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
+        self.repair().await?;
         self.change_i2c_address_direct(new_address).await
     }
 
+    /// Same functionality as [`set_ambient_analogue_gain_level()`](VL6180X::set_ambient_analogue_gain_level)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_set_ambient_analogue_gain_level(
+        &mut self,
+        level: u8,
+    ) -> Result<(), Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        let integration_period = self.config.ambient_integration_period;
+        self.set_ambient_gain_and_integration_direct(level, integration_period)
+            .await
+    }
+
+    /// Same functionality as [`set_ambient_integration_period()`](VL6180X::set_ambient_integration_period)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_set_ambient_integration_period(
+        &mut self,
+        time: Milliseconds,
+    ) -> Result<(), Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        let gain_level = self.config.ambient_analogue_gain_level;
+        self.set_ambient_gain_and_integration_direct(gain_level, time.0)
+            .await
+    }
+
+    /// Same functionality as [`set_range_result_scaler()`](VL6180X::set_range_result_scaler)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_set_range_result_scaler(&mut self, scaler: u8) -> Result<(), Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.set_range_result_scaler_direct(scaler).await
+    }
+
+    /// Same functionality as [`set_range_interrupt()`](VL6180X::set_range_interrupt)
+    /// but with a check on the current [OperatingMode].
+    /// Valid in all OperatingModes except [PoweredOff],
+    /// in which case will return [Error::InvalidMethod]
+    pub async fn try_set_range_interrupt(
+        &mut self,
+        interrupt_mode: RangeInterruptMode,
+        low_threshold: u8,
+        high_threshold: u8,
+    ) -> Result<(), Error<E>> {
+        if self.mode.operating_mode == PoweredOff {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.set_range_interrupt_direct(interrupt_mode, low_threshold, high_threshold)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the [Config] and writes it to the device. The I2C address is kept, use
+    /// [`try_change_i2c_address()`](VL6180X::try_change_i2c_address) to change it.
+    /// Valid when OperatingMode is [Ready],
+    /// otherwise returns [Error::InvalidMethod]
+    pub async fn try_set_config(&mut self, config: &Config) -> Result<(), Error<E>> {
+        if self.mode.operating_mode != Ready {
+            return Err(Error::InvalidMethod(self.mode.operating_mode));
+        }
+        self.repair().await?;
+        self.set_config_direct(config).await?;
+        Ok(())
+    }
+
     /// Same functionality as [`power_off()`](VL6180X::power_off)
     /// but with a check on the current [OperatingMode].
     /// Valid in all OperatingModes except [PoweredOff],
@@ -287,6 +770,7 @@ where
             return Err(Error::InvalidMethod(self.mode.operating_mode));
         }
         self.power_off_direct(x_shutdown_pin)?;
+        self.device_off();
         self.mode.operating_mode = PoweredOff;
         Ok(())
     }
@@ -302,8 +786,47 @@ where
         if self.mode.operating_mode != PoweredOff {
             return Err(Error2::InvalidMethod(self.mode.operating_mode));
         }
-        self.power_on_and_init_direct(x_shutdown_pin).await?;
+        self.power_on_device(x_shutdown_pin).await?;
         self.mode.operating_mode = Ready;
         Ok(())
     }
+
+    /// Same functionality as [`hardware_reset()`](VL6180X::hardware_reset).
+    /// Valid in all OperatingModes, the OperatingMode is [Ready] afterwards.
+    pub async fn hardware_reset<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        self.reset_device(x_shutdown_pin, delay).await?;
+        self.mode.operating_mode = Ready;
+        Ok(())
+    }
+
+    /// Same functionality as [`recover()`](VL6180X::recover).
+    /// Valid in all OperatingModes, afterwards the OperatingMode is the same as before,
+    /// except for [PoweredOff] which becomes [Ready].
+    ///
+    /// If the recovery fails the OperatingMode is left unchanged, so that the
+    /// recovery can be retried.
+    pub async fn recover<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &mut self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        let operating_mode = match self.mode.operating_mode {
+            PoweredOff => Ready,
+            operating_mode => operating_mode,
+        };
+        self.reset_device(x_shutdown_pin, delay).await?;
+        self.drive_device(operating_mode)
+            .await
+            .map_err(|e| match e {
+                Error::BusError(e) => Error2::BusError(e),
+                // Starting from an idle device only writes registers
+                _ => Error2::Timeout,
+            })?;
+        self.mode.operating_mode = operating_mode;
+        Ok(())
+    }
 }
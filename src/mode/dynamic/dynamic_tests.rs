@@ -0,0 +1,582 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::mock::{self, poll_then_drop, MockDelay, MockI2c, MockPin};
+
+const ALL_MODES: [OperatingMode; 5] = [
+    PoweredOff,
+    Ready,
+    RangeContinuous,
+    AmbientContinuous,
+    InterleavedContinuous,
+];
+const ALL_EXCEPT_POWERED_OFF: [OperatingMode; 4] = [
+    Ready,
+    RangeContinuous,
+    AmbientContinuous,
+    InterleavedContinuous,
+];
+
+fn dynamic_sensor(operating_mode: OperatingMode) -> VL6180X<DynamicMode, MockI2c> {
+    let mut sensor = mock::sensor(DynamicMode {
+        operating_mode,
+        device: DeviceState::settled(operating_mode),
+    });
+    // Range and ambient samples ready without errors, so reads succeed
+    sensor.com.set(0x04F, 0b00_100_100);
+    sensor.com.range_running = operating_mode == RangeContinuous;
+    sensor.com.ambient_running =
+        operating_mode == AmbientContinuous || operating_mode == InterleavedContinuous;
+    sensor
+        .com
+        .set(0x2A3, (operating_mode == InterleavedContinuous) as u8);
+    sensor
+}
+
+/// Checks that the mock device takes the measurements of `mode`.
+fn assert_device_in(sensor: &VL6180X<DynamicMode, MockI2c>, mode: OperatingMode) {
+    assert_eq!(sensor.operating_mode(), mode);
+    assert_eq!(
+        sensor.com.range_running,
+        mode == RangeContinuous,
+        "{:?}",
+        mode
+    );
+    assert_eq!(
+        sensor.com.ambient_running,
+        mode == AmbientContinuous || mode == InterleavedContinuous,
+        "{:?}",
+        mode
+    );
+    assert_eq!(
+        sensor.com.registers[0x2A3],
+        (mode == InterleavedContinuous) as u8,
+        "{:?}",
+        mode
+    );
+}
+
+/// Calls the method in every [OperatingMode] and checks it only succeeds in `valid`.
+/// Rejected calls must not send anything to the device.
+macro_rules! assert_valid_in {
+    ($valid:expr, $method:ident($($arg:expr),*)) => {
+        for mode in ALL_MODES {
+            let mut sensor = dynamic_sensor(mode);
+            let result = block_on(sensor.$method($($arg),*));
+            if $valid.contains(&mode) {
+                assert!(result.is_ok(), "{} in {:?}", stringify!($method), mode);
+            } else {
+                assert_eq!(
+                    result.err(),
+                    Some(Error::InvalidMethod(mode)),
+                    "{} in {:?}",
+                    stringify!($method),
+                    mode
+                );
+                assert!(sensor.com.writes.is_empty());
+                assert_eq!(sensor.mode.operating_mode, mode);
+            }
+        }
+    };
+}
+
+#[test]
+fn try_poll_range_mm_single_blocking_modes() {
+    assert_valid_in!([Ready], try_poll_range_mm_single_blocking());
+}
+
+#[test]
+fn try_poll_range_single_blocking_modes() {
+    assert_valid_in!([Ready], try_poll_range_single_blocking());
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn try_poll_ambient_lux_single_blocking_modes() {
+    assert_valid_in!([Ready], try_poll_ambient_lux_single_blocking());
+}
+
+#[test]
+fn try_poll_ambient_milli_lux_single_blocking_modes() {
+    assert_valid_in!([Ready], try_poll_ambient_milli_lux_single_blocking());
+}
+
+#[test]
+fn try_start_range_continuous_mode_modes() {
+    assert_valid_in!([Ready], try_start_range_continuous_mode());
+}
+
+#[test]
+fn try_stop_range_continuous_mode_modes() {
+    assert_valid_in!([RangeContinuous], try_stop_range_continuous_mode());
+}
+
+#[test]
+fn try_start_ambient_continuous_mode_modes() {
+    assert_valid_in!([Ready], try_start_ambient_continuous_mode());
+}
+
+#[test]
+fn try_stop_ambient_continuous_mode_modes() {
+    assert_valid_in!([AmbientContinuous], try_stop_ambient_continuous_mode());
+}
+
+#[test]
+fn try_start_interleaved_continuous_mode_modes() {
+    assert_valid_in!([Ready], try_start_interleaved_continuous_mode());
+}
+
+#[test]
+fn try_stop_interleaved_continuous_mode_modes() {
+    assert_valid_in!(
+        [InterleavedContinuous],
+        try_stop_interleaved_continuous_mode()
+    );
+}
+
+#[test]
+fn try_start_range_single_modes() {
+    assert_valid_in!([Ready, AmbientContinuous], try_start_range_single());
+}
+
+#[test]
+fn try_start_ambient_single_modes() {
+    assert_valid_in!([Ready, RangeContinuous], try_start_ambient_single());
+}
+
+#[test]
+fn try_read_range_mm_blocking_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_range_mm_blocking());
+}
+
+#[test]
+fn try_read_range_mm_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_range_mm());
+}
+
+#[test]
+fn try_read_range_blocking_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_range_blocking());
+}
+
+#[test]
+fn try_read_range_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_range());
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn try_read_ambient_lux_blocking_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_ambient_lux_blocking());
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn try_read_ambient_lux_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_ambient_lux());
+}
+
+#[test]
+fn try_read_ambient_milli_lux_blocking_modes() {
+    assert_valid_in!(
+        ALL_EXCEPT_POWERED_OFF,
+        try_read_ambient_milli_lux_blocking()
+    );
+}
+
+#[test]
+fn try_read_ambient_milli_lux_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_ambient_milli_lux());
+}
+
+#[test]
+fn try_read_ambient_blocking_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_ambient_blocking());
+}
+
+#[test]
+fn try_read_ambient_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_ambient());
+}
+
+#[test]
+fn try_read_range_return_rate_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_range_return_rate());
+}
+
+#[test]
+fn try_clear_error_interrupt_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_clear_error_interrupt());
+}
+
+#[test]
+fn try_clear_ambient_interrupt_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_clear_ambient_interrupt());
+}
+
+#[test]
+fn try_clear_range_interrupt_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_clear_range_interrupt());
+}
+
+#[test]
+fn try_clear_all_interrupts_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_clear_all_interrupts());
+}
+
+#[test]
+fn try_change_i2c_address_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_change_i2c_address(0x30));
+}
+
+#[test]
+fn try_set_range_interrupt_modes() {
+    assert_valid_in!(
+        ALL_EXCEPT_POWERED_OFF,
+        try_set_range_interrupt(RangeInterruptMode::OutOfWindow, 10, 20)
+    );
+}
+
+#[test]
+fn try_set_range_result_scaler_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_set_range_result_scaler(2));
+}
+
+#[test]
+fn try_set_ambient_analogue_gain_level_modes() {
+    assert_valid_in!(
+        ALL_EXCEPT_POWERED_OFF,
+        try_set_ambient_analogue_gain_level(7)
+    );
+}
+
+#[test]
+fn try_set_ambient_integration_period_modes() {
+    assert_valid_in!(
+        ALL_EXCEPT_POWERED_OFF,
+        try_set_ambient_integration_period(Milliseconds(50))
+    );
+}
+
+#[test]
+fn try_power_off_modes() {
+    for mode in ALL_MODES {
+        let mut sensor = dynamic_sensor(mode);
+        let mut pin = MockPin::new(true);
+        let result = sensor.try_power_off(&mut pin);
+        if mode == PoweredOff {
+            assert_eq!(result, Err(Error::InvalidMethod(mode)));
+            assert!(pin.is_high);
+        } else {
+            assert_eq!(result, Ok(()));
+            assert!(!pin.is_high);
+            assert_eq!(sensor.mode.operating_mode, PoweredOff);
+        }
+    }
+}
+
+#[test]
+fn try_power_on_and_init_modes() {
+    for mode in ALL_MODES {
+        let mut sensor = dynamic_sensor(mode);
+        let mut pin = MockPin::default();
+        let result = block_on(sensor.try_power_on_and_init(&mut pin));
+        if mode == PoweredOff {
+            assert_eq!(result, Ok(()));
+            assert!(pin.is_high);
+            assert_eq!(sensor.mode.operating_mode, Ready);
+        } else {
+            assert_eq!(result, Err(Error2::InvalidMethod(mode)));
+            assert!(!pin.is_high);
+            assert!(sensor.com.writes.is_empty());
+        }
+    }
+}
+
+#[test]
+fn try_start_range_single_sends_single_start() {
+    let mut sensor = dynamic_sensor(AmbientContinuous);
+    block_on(sensor.try_start_range_single()).unwrap();
+    assert!(sensor.com.was_written(0x018, 0b01));
+}
+
+#[test]
+fn try_start_ambient_single_sends_single_start() {
+    let mut sensor = dynamic_sensor(RangeContinuous);
+    block_on(sensor.try_start_ambient_single()).unwrap();
+    assert!(sensor.com.was_written(0x038, 0b01));
+}
+
+#[test]
+fn set_operating_mode_between_all_powered_on_modes() {
+    for current in ALL_EXCEPT_POWERED_OFF {
+        for target in ALL_EXCEPT_POWERED_OFF {
+            let mut sensor = dynamic_sensor(current);
+            assert_eq!(block_on(sensor.set_operating_mode(target)), Ok(()));
+            assert_eq!(sensor.mode.operating_mode, target);
+            if current == target {
+                assert!(sensor.com.writes.is_empty());
+            }
+        }
+    }
+}
+
+#[test]
+fn set_operating_mode_rejects_powered_off() {
+    let mut sensor = dynamic_sensor(PoweredOff);
+    assert_eq!(
+        block_on(sensor.set_operating_mode(Ready)),
+        Err(Error::InvalidMethod(PoweredOff))
+    );
+    let mut sensor = dynamic_sensor(RangeContinuous);
+    assert_eq!(
+        block_on(sensor.set_operating_mode(PoweredOff)),
+        Err(Error::InvalidMethod(RangeContinuous))
+    );
+    assert!(sensor.com.writes.is_empty());
+}
+
+#[test]
+fn set_operating_mode_interleaved_to_ambient() {
+    let mut sensor = dynamic_sensor(InterleavedContinuous);
+    block_on(sensor.set_operating_mode(AmbientContinuous)).unwrap();
+    assert_eq!(
+        sensor.com.writes,
+        [(0x038, 0b11), (0x2A3, 0), (0x038, 0b11)]
+    );
+}
+
+#[test]
+fn set_operating_mode_is_ready_if_stopped_but_not_restarted() {
+    let mut sensor = dynamic_sensor(RangeContinuous);
+    sensor.config.ambient_inter_measurement_period = 10;
+    assert_eq!(
+        block_on(sensor.set_operating_mode(InterleavedContinuous)),
+        Err(Error::InvalidConfigurationValue(10))
+    );
+    assert_eq!(sensor.mode.operating_mode, RangeContinuous);
+}
+
+#[test]
+fn operating_mode_getter() {
+    for mode in ALL_MODES {
+        assert_eq!(dynamic_sensor(mode).operating_mode(), mode);
+    }
+}
+
+#[test]
+fn try_into_typed_modes() {
+    for mode in ALL_MODES {
+        assert_eq!(
+            dynamic_sensor(mode).try_into_ready_mode().is_ok(),
+            mode == Ready
+        );
+        assert_eq!(
+            dynamic_sensor(mode)
+                .try_into_range_continuous_mode()
+                .is_ok(),
+            mode == RangeContinuous
+        );
+        assert_eq!(
+            dynamic_sensor(mode)
+                .try_into_ambient_continuous_mode()
+                .is_ok(),
+            mode == AmbientContinuous
+        );
+        assert_eq!(
+            dynamic_sensor(mode)
+                .try_into_interleaved_continuous_mode()
+                .is_ok(),
+            mode == InterleavedContinuous
+        );
+        assert_eq!(
+            dynamic_sensor(mode).try_into_powered_off_mode().is_ok(),
+            mode == PoweredOff
+        );
+    }
+}
+
+#[test]
+fn try_into_typed_mode_returns_driver_on_mismatch() {
+    let sensor = dynamic_sensor(RangeContinuous);
+    let sensor = sensor.try_into_ready_mode().unwrap_err();
+    assert_eq!(sensor.operating_mode(), RangeContinuous);
+    assert!(sensor.try_into_range_continuous_mode().is_ok());
+}
+
+#[test]
+fn dynamic_round_trip_keeps_config() {
+    let mut sensor = mock::sensor(ReadyMode).into_dynamic_mode();
+    block_on(sensor.try_change_i2c_address(0x30)).unwrap();
+    let sensor = sensor.try_into_ready_mode().unwrap();
+    assert_eq!(sensor.config.address, 0x30);
+}
+
+#[test]
+fn set_config_keeps_the_i2c_address() {
+    let mut sensor = dynamic_sensor(Ready);
+    block_on(sensor.try_change_i2c_address(0x30)).unwrap();
+    block_on(sensor.try_set_config(&Config::new())).unwrap();
+    assert_eq!(sensor.config.address, 0x30);
+    assert_eq!(block_on(sensor.read_model_id_direct()), Ok(0xB4));
+}
+
+#[test]
+fn hardware_reset_is_valid_in_all_modes() {
+    for mode in ALL_MODES {
+        let mut sensor = dynamic_sensor(mode);
+        let mut pin = MockPin::new(mode != PoweredOff);
+        let mut delay = MockDelay::default();
+        block_on(sensor.hardware_reset(&mut pin, &mut delay)).unwrap();
+        assert_eq!(pin.history, [false, true]);
+        assert_eq!(sensor.mode.operating_mode, Ready);
+    }
+}
+
+#[test]
+fn recover_restores_address_and_operating_mode() {
+    for mode in ALL_EXCEPT_POWERED_OFF {
+        let mut sensor = dynamic_sensor(mode);
+        block_on(sensor.try_change_i2c_address(0x30)).unwrap();
+        // The reset makes the device answer to the default address again
+        sensor.com.device_address = 0x29;
+        sensor.com.writes.clear();
+
+        let mut pin = MockPin::new(true);
+        block_on(sensor.recover(&mut pin, &mut MockDelay::default())).unwrap();
+
+        assert_eq!(pin.history, [false, true]);
+        assert_eq!(sensor.operating_mode(), mode);
+        assert_eq!(sensor.config.address, 0x30);
+        assert_eq!(sensor.com.device_address, 0x30);
+        assert_eq!(block_on(sensor.read_model_id_direct()), Ok(0xB4));
+        if mode == RangeContinuous {
+            assert_eq!(sensor.com.writes.last(), Some(&(0x018, 0b11)));
+        }
+    }
+}
+
+#[test]
+fn recover_failure_keeps_operating_mode() {
+    let mut sensor = dynamic_sensor(InterleavedContinuous);
+    // Device never boots
+    sensor.com.set(0x016, 0);
+    let mut pin = MockPin::new(true);
+    assert_eq!(
+        block_on(sensor.recover(&mut pin, &mut MockDelay::default())),
+        Err(Error2::Timeout)
+    );
+    assert_eq!(sensor.operating_mode(), InterleavedContinuous);
+}
+
+#[test]
+fn try_read_device_errors_modes() {
+    assert_valid_in!(ALL_EXCEPT_POWERED_OFF, try_read_device_errors());
+}
+
+#[test]
+fn set_operating_mode_dropped_at_every_transaction() {
+    for current in ALL_EXCEPT_POWERED_OFF {
+        for target in ALL_EXCEPT_POWERED_OFF {
+            for polls in 1.. {
+                let mut sensor = dynamic_sensor(current);
+                sensor.com.yield_each_transaction = true;
+                let completed = poll_then_drop(sensor.set_operating_mode(target), polls);
+                let reported = sensor.operating_mode();
+                assert_eq!(reported, if completed { target } else { current });
+
+                // The next call first brings the device back to the reported mode
+                block_on(sensor.set_operating_mode(reported)).unwrap();
+                assert_device_in(&sensor, reported);
+                block_on(sensor.set_operating_mode(target)).unwrap();
+                assert_device_in(&sensor, target);
+                if completed {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn interrupted_start_and_stop_are_repaired_by_any_call() {
+    for polls in 1.. {
+        let mut sensor = dynamic_sensor(InterleavedContinuous);
+        sensor.com.yield_each_transaction = true;
+        let completed = poll_then_drop(sensor.try_stop_interleaved_continuous_mode(), polls);
+        let reported = sensor.operating_mode();
+        block_on(sensor.try_read_ambient()).unwrap();
+        assert_device_in(&sensor, reported);
+        if completed {
+            assert_eq!(reported, Ready);
+            break;
+        }
+    }
+    for polls in 1.. {
+        let mut sensor = dynamic_sensor(Ready);
+        sensor.com.yield_each_transaction = true;
+        let completed = poll_then_drop(sensor.try_start_range_continuous_mode(), polls);
+        let reported = sensor.operating_mode();
+        block_on(sensor.try_read_device_errors()).unwrap();
+        assert_device_in(&sensor, reported);
+        if completed {
+            assert_eq!(reported, RangeContinuous);
+            break;
+        }
+    }
+}
+
+#[test]
+fn try_power_on_and_init_dropped_at_every_transaction() {
+    for polls in 1.. {
+        let mut sensor = dynamic_sensor(PoweredOff);
+        sensor.config.address = 0x30;
+        sensor.com.yield_each_transaction = true;
+        let mut pin = MockPin::new(false);
+        let completed = poll_then_drop(sensor.try_power_on_and_init(&mut pin), polls);
+        if !completed {
+            assert_eq!(sensor.operating_mode(), PoweredOff);
+            assert_eq!(
+                block_on(sensor.try_read_device_errors()),
+                Err(Error::InvalidMethod(PoweredOff))
+            );
+            // Carries on with the initialization without resetting the device
+            block_on(sensor.try_power_on_and_init(&mut pin)).unwrap();
+        }
+        assert!(pin.history.iter().all(|&high| high));
+        assert_device_in(&sensor, Ready);
+        assert_eq!(sensor.config.address, 0x30);
+        assert_eq!(sensor.com.device_address, 0x30);
+        assert_eq!(sensor.com.registers[0x016], 0);
+        if completed {
+            break;
+        }
+    }
+}
+
+#[test]
+fn recover_dropped_at_every_transaction() {
+    for mode in ALL_EXCEPT_POWERED_OFF {
+        for polls in 1.. {
+            let mut sensor = dynamic_sensor(mode);
+            block_on(sensor.try_change_i2c_address(0x30)).unwrap();
+            sensor.com.reset();
+            sensor.com.yield_each_transaction = true;
+            let mut pin = MockPin::new(true);
+            let completed =
+                poll_then_drop(sensor.recover(&mut pin, &mut MockDelay::default()), polls);
+            if !completed {
+                assert_eq!(sensor.operating_mode(), mode);
+                // The retry holds the device in reset again
+                sensor.com.reset();
+                block_on(sensor.recover(&mut pin, &mut MockDelay::default())).unwrap();
+            }
+            assert_device_in(&sensor, mode);
+            assert_eq!(sensor.config.address, 0x30);
+            assert_eq!(sensor.com.device_address, 0x30);
+            if completed {
+                break;
+            }
+        }
+    }
+}
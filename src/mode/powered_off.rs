@@ -1,13 +1,23 @@
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::i2c::I2c;
 
-use crate::{error::Error2, mode::ReadyMode, VL6180X};
+use crate::{
+    error::Error2,
+    mode::{CurrentOperatingMode, OperatingMode, ReadyMode},
+    VL6180X,
+};
 
 /// Mode in which the sensor is powered off.
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub struct PoweredOffMode {}
 
+impl CurrentOperatingMode for PoweredOffMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        OperatingMode::PoweredOff
+    }
+}
+
 impl<I2C, E> VL6180X<PoweredOffMode, I2C>
 where
     I2C: I2c<Error = E>,
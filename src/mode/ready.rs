@@ -1,10 +1,14 @@
 use embedded_hal_async::i2c::I2c;
 
 use super::{
-    AllowReadMeasurement, AllowStartAmbientSingle, AllowStartRangeSingle,
-    AmbientContinuousMode, DynamicMode, InterleavedContinuousMode, RangeContinuousMode,
+    AllowReadMeasurement, AllowRecovery, AllowStartAmbientSingle, AllowStartRangeSingle,
+    AmbientContinuousMode, CurrentOperatingMode, DynamicMode, InterleavedContinuousMode,
+    OperatingMode, RangeContinuousMode,
 };
-use crate::{error::Error, AllowCommunication, Config, VL6180X};
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{MilliLux, Millimeters};
+use crate::{error::Error, AllowCommunication, Config, Range, VL6180X};
 /// Sensor has been configured and is ready to take single measurements or switch to a
 /// continuous measurement mode
 #[derive(Debug, Copy, Clone)]
@@ -19,6 +23,16 @@ impl AllowStartRangeSingle for ReadyMode {}
 
 impl AllowStartAmbientSingle for ReadyMode {}
 
+impl AllowRecovery for ReadyMode {
+    const OPERATING_MODE: OperatingMode = OperatingMode::Ready;
+}
+
+impl CurrentOperatingMode for ReadyMode {
+    fn current_operating_mode(&self) -> OperatingMode {
+        OperatingMode::Ready
+    }
+}
+
 impl<I2C, E> VL6180X<ReadyMode, I2C>
 where
     I2C: I2c<Error = E>,
@@ -59,17 +73,35 @@ where
     /// Poll the sensor for a single range measurement.
     /// Starts a single range measurement then calls [`read_range_mm_blocking`](VL6180X::read_range_mm_blocking)
     /// to wait for the result.
-    pub async fn poll_range_mm_single_blocking(&mut self) -> Result<u16, Error<E>> {
+    pub async fn poll_range_mm_single_blocking(&mut self) -> Result<Millimeters, Error<E>> {
         self.poll_range_mm_single_blocking_direct().await
     }
 
+    /// Poll the sensor for a single range measurement, returning no target
+    /// or out of range as a [Range] instead of an error.
+    /// Starts a single range measurement then calls [`read_range_blocking`](VL6180X::read_range_blocking)
+    /// to wait for the result.
+    pub async fn poll_range_single_blocking(&mut self) -> Result<Range, Error<E>> {
+        self.poll_range_single_blocking_direct().await
+    }
+
     /// Poll the sensor for a single ambient light measurement.
     /// Starts a single ambient measurement then calls [`read_ambient_lux_blocking`](VL6180X::read_ambient_lux_blocking)
     /// to wait for the result.
-    pub async fn poll_ambient_lux_single_blocking(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub async fn poll_ambient_lux_single_blocking(&mut self) -> Result<Lux, Error<E>> {
         self.poll_ambient_lux_single_blocking_direct().await
     }
 
+    /// Poll the sensor for a single ambient light measurement in milli-lux.
+    /// Starts a single ambient measurement then calls [`read_ambient_milli_lux_blocking`](VL6180X::read_ambient_milli_lux_blocking)
+    /// to wait for the result.
+    pub async fn poll_ambient_milli_lux_single_blocking(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        self.poll_ambient_milli_lux_single_blocking_direct().await
+    }
+
     /// Starts continuous operation mode for reading range measurements.
     ///
     /// Main configuration values are:
@@ -0,0 +1,248 @@
+use core::{convert::TryFrom, task::Poll};
+
+use embedded_hal::i2c::I2c as BlockingI2c;
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    error::Error,
+    mode::{AllowStartAmbientSingle, AllowStartRangeSingle},
+    read_measurements::convert_raw_ambient_to_milli_lux,
+    register::{
+        AmbientStatusErrorCode, InterruptErrorCode, InterruptStatus, RangeStatusErrorCode,
+        Register8Bit, SysAmbientStartCode, SysInterruptClearCode, SysRangeStartCode,
+    },
+    units::{Millimeters, RawAmbientCount},
+    Config, Range, Sample, VL6180X,
+};
+
+#[cfg(test)]
+mod poller_tests;
+
+/// Takes single shot measurements with a blocking I2C bus, one step per
+/// [`poll()`](SingleShotPoller::poll), for a main loop that services other things in
+/// between.
+///
+/// Each call does at most one I2C transaction: it starts a measurement, checks
+/// RESULT__INTERRUPT_STATUS_GPIO until the result is ready, reads the result and then
+/// clears the interrupt, returning the [Sample]. The next call starts a new
+/// measurement. Giving up after [poll_max_loop](Config::set_poll_max_loop) checks
+/// returns [Error::Timeout]. A check finding a laser safety or PLL error is followed
+/// by a call clearing the error interrupt, like
+/// [`read_device_errors()`](VL6180X::read_device_errors) does, which returns the error.
+///
+/// A call that fails with a bus error is repeated by the next one. Created with
+/// [`VL6180X::range_poller()`] or [`VL6180X::ambient_poller()`], it keeps a copy of
+/// the config, create a new one after changing it.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct SingleShotPoller {
+    config: Config,
+    ambient: bool,
+    step: Step,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+enum Step {
+    Start,
+    Check { checks: u16 },
+    Read,
+    Clear { status: u8, value: u16 },
+    ClearError { error: InterruptErrorCode },
+}
+
+impl<MODE, I2C> VL6180X<MODE, I2C>
+where
+    MODE: AllowStartRangeSingle,
+    I2C: I2c,
+{
+    /// Returns a [SingleShotPoller] taking range measurements.
+    pub fn range_poller(&self) -> SingleShotPoller {
+        SingleShotPoller::new(self.config, false)
+    }
+}
+
+impl<MODE, I2C> VL6180X<MODE, I2C>
+where
+    MODE: AllowStartAmbientSingle,
+    I2C: I2c,
+{
+    /// Returns a [SingleShotPoller] taking ambient light measurements.
+    pub fn ambient_poller(&self) -> SingleShotPoller {
+        SingleShotPoller::new(self.config, true)
+    }
+}
+
+impl SingleShotPoller {
+    fn new(config: Config, ambient: bool) -> Self {
+        SingleShotPoller {
+            config,
+            ambient,
+            step: Step::Start,
+        }
+    }
+
+    /// Returns whether a measurement is in progress, i.e. the next
+    /// [`poll()`](SingleShotPoller::poll) does not start a new one.
+    pub fn is_busy(&self) -> bool {
+        self.step != Step::Start
+    }
+
+    /// Carries out the next step, returning the [Sample] once it is read.
+    /// `i2c` must reach the same device as the [VL6180X] it was created from.
+    pub fn poll<B: BlockingI2c>(
+        &mut self,
+        i2c: &mut B,
+    ) -> Poll<Result<Sample, Error<B::Error>>> {
+        match self.step(i2c) {
+            Ok(Some(sample)) => Poll::Ready(Ok(sample)),
+            Ok(None) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    fn step<B: BlockingI2c>(
+        &mut self,
+        i2c: &mut B,
+    ) -> Result<Option<Sample>, Error<B::Error>> {
+        match self.step {
+            Step::Start => {
+                if self.ambient {
+                    self.write(
+                        i2c,
+                        Register8Bit::SYSALS__START,
+                        SysAmbientStartCode::SingleStart as u8,
+                    )?;
+                } else {
+                    self.write(
+                        i2c,
+                        Register8Bit::SYSRANGE__START,
+                        SysRangeStartCode::SingleStart as u8,
+                    )?;
+                }
+                self.step = Step::Check { checks: 0 };
+            }
+            Step::Check { checks } => {
+                let mut status = [0];
+                self.read(
+                    i2c,
+                    Register8Bit::RESULT__INTERRUPT_STATUS_GPIO,
+                    &mut status,
+                )?;
+                let status = InterruptStatus::try_from(status[0])
+                    .map_err(|status| self.fail(Error::UnknownRegisterCode(status)))?;
+                if status.error != InterruptErrorCode::NoError {
+                    self.step = Step::ClearError {
+                        error: status.error,
+                    };
+                    return Ok(None);
+                }
+                let ready = if self.ambient {
+                    status.ambient_ready()
+                } else {
+                    status.range_ready()
+                };
+                self.step = if ready {
+                    Step::Read
+                } else if checks + 1 >= self.config.poll_max_loop {
+                    return Err(self.fail(Error::Timeout));
+                } else {
+                    Step::Check { checks: checks + 1 }
+                };
+            }
+            Step::Read => {
+                // The status registers up to and including the result value
+                self.step = if self.ambient {
+                    let mut data = [0; 4];
+                    self.read(i2c, Register8Bit::RESULT__ALS_STATUS, &mut data)?;
+                    Step::Clear {
+                        status: data[0],
+                        value: u16::from_be_bytes([data[2], data[3]]),
+                    }
+                } else {
+                    let mut data = [0; (Register8Bit::RESULT__RANGE_VAL as usize -
+                        Register8Bit::RESULT__RANGE_STATUS as usize) +
+                        1];
+                    self.read(i2c, Register8Bit::RESULT__RANGE_STATUS, &mut data)?;
+                    Step::Clear {
+                        status: data[0],
+                        value: data[data.len() - 1] as u16,
+                    }
+                };
+            }
+            Step::Clear { status, value } => {
+                let code = if self.ambient {
+                    SysInterruptClearCode::Ambient
+                } else {
+                    SysInterruptClearCode::Range
+                };
+                self.write(i2c, Register8Bit::SYSTEM__INTERRUPT_CLEAR, code as u8)?;
+                self.step = Step::Start;
+                return self.decode(status, value).map(Some);
+            }
+            Step::ClearError { error } => {
+                self.write(
+                    i2c,
+                    Register8Bit::SYSTEM__INTERRUPT_CLEAR,
+                    SysInterruptClearCode::Error as u8,
+                )?;
+                let error = match error {
+                    InterruptErrorCode::LaserSafetyError => Error::LaserSafetyError,
+                    // The check only moves here with an error
+                    _ => Error::PllError,
+                };
+                return Err(self.fail(error));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Ends the measurement with `error`, the next call starts a new one.
+    fn fail<E>(&mut self, error: Error<E>) -> Error<E> {
+        self.step = Step::Start;
+        error
+    }
+
+    fn decode<E>(&self, status: u8, value: u16) -> Result<Sample, Error<E>> {
+        if self.ambient {
+            let error = AmbientStatusErrorCode::try_from(status)
+                .map_err(|_| Error::UnknownRegisterCode(status))?;
+            if error != AmbientStatusErrorCode::NoError {
+                return Err(Error::AmbientStatusError(error));
+            }
+            return Ok(Sample::Ambient(convert_raw_ambient_to_milli_lux(
+                RawAmbientCount(value),
+                self.config.ambient_analogue_gain_level,
+                self.config.ambient_integration_period,
+            )));
+        }
+        let error = RangeStatusErrorCode::try_from(status)
+            .map_err(|_| Error::UnknownRegisterCode(status))?;
+        let range = match error {
+            RangeStatusErrorCode::NoError => {
+                Range::Mm(Millimeters(self.config.range_scaling as u16 * value))
+            }
+            error => Range::from_status_error(error).ok_or(Error::RangeStatusError(error))?,
+        };
+        Ok(Sample::Range(range))
+    }
+
+    fn read<B: BlockingI2c>(
+        &self,
+        i2c: &mut B,
+        reg: Register8Bit,
+        data: &mut [u8],
+    ) -> Result<(), B::Error> {
+        i2c.write_read(self.config.address, &(reg as u16).to_be_bytes(), data)
+    }
+
+    fn write<B: BlockingI2c>(
+        &self,
+        i2c: &mut B,
+        reg: Register8Bit,
+        code: u8,
+    ) -> Result<(), B::Error> {
+        let reg = (reg as u16).to_be_bytes();
+        i2c.write(self.config.address, &[reg[0], reg[1], code])
+    }
+}
@@ -0,0 +1,144 @@
+use embedded_hal::i2c::ErrorKind;
+
+use super::*;
+use crate::{
+    mock::{self, MockI2c},
+    units::MilliLux,
+    ReadyMode,
+};
+
+/// Polls until a result, checking every call does at most one transaction, or two
+/// when it clears a device error. Returns the result and the number of calls.
+fn poll_to_end(
+    poller: &mut SingleShotPoller,
+    i2c: &mut MockI2c,
+) -> (Result<Sample, Error<ErrorKind>>, usize) {
+    for calls in 1..1000 {
+        let before = i2c.transactions;
+        let result = poller.poll(i2c);
+        assert!(i2c.transactions - before <= 1);
+        if let Poll::Ready(result) = result {
+            return (result, calls);
+        }
+    }
+    panic!("never finished");
+}
+
+#[test]
+fn range_single_shot_step_by_step() {
+    let sensor = mock::sensor(ReadyMode);
+    let mut poller = sensor.range_poller();
+    let mut i2c = sensor.com;
+    i2c.set(0x062, 50);
+
+    assert!(!poller.is_busy());
+    assert_eq!(poller.poll(&mut i2c), Poll::Pending);
+    assert!(i2c.was_written(0x018, 0b01));
+    assert!(poller.is_busy());
+    assert_eq!(poller.poll(&mut i2c), Poll::Pending);
+    assert_eq!(poller.poll(&mut i2c), Poll::Pending);
+    // The measurement finishes
+    i2c.set(0x04F, 0b00_000_100);
+    let (result, calls) = poll_to_end(&mut poller, &mut i2c);
+    assert_eq!(result, Ok(Sample::Range(Range::Mm(Millimeters(50)))));
+    // Check, read and clear
+    assert_eq!(calls, 3);
+    assert_eq!(i2c.writes.last(), Some(&(0x015, 0b001)));
+    assert_eq!(i2c.transactions, 6);
+    assert!(!poller.is_busy());
+}
+
+#[test]
+fn ambient_single_shot() {
+    let sensor = mock::sensor(ReadyMode);
+    let mut poller = sensor.ambient_poller();
+    let config = sensor.config;
+    let mut i2c = sensor.com;
+    i2c.set(0x04F, 0b00_100_000);
+    i2c.set(0x050, 0x01);
+    i2c.set(0x051, 0x00);
+    let expected: MilliLux = convert_raw_ambient_to_milli_lux(
+        RawAmbientCount(0x100),
+        config.ambient_analogue_gain_level,
+        config.ambient_integration_period,
+    );
+    assert_eq!(
+        poll_to_end(&mut poller, &mut i2c).0,
+        Ok(Sample::Ambient(expected))
+    );
+    assert!(i2c.was_written(0x038, 0b01));
+    assert_eq!(i2c.writes.last(), Some(&(0x015, 0b010)));
+}
+
+#[test]
+fn range_status_errors_are_returned_after_clearing() {
+    let sensor = mock::sensor(ReadyMode);
+    let mut poller = sensor.range_poller();
+    let mut i2c = sensor.com;
+    i2c.set(0x04F, 0b00_000_100);
+    i2c.set(0x04D, 0b0111_0001);
+    assert_eq!(
+        poll_to_end(&mut poller, &mut i2c).0,
+        Ok(Sample::Range(Range::NoTarget))
+    );
+    i2c.set(0x04F, 0b00_000_100);
+    i2c.set(0x04D, 0b0100_0001);
+    assert_eq!(
+        poll_to_end(&mut poller, &mut i2c).0,
+        Err(Error::RangeStatusError(RangeStatusErrorCode::Pll1Lock))
+    );
+    assert_eq!(i2c.registers[0x04F], 0);
+}
+
+#[test]
+fn times_out_after_poll_max_loop_checks() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.config.set_poll_max_loop(3);
+    let mut poller = sensor.range_poller();
+    let mut i2c = sensor.com;
+    let (result, calls) = poll_to_end(&mut poller, &mut i2c);
+    assert_eq!(result, Err(Error::Timeout));
+    // Start and 3 checks
+    assert_eq!(calls, 4);
+    assert!(!poller.is_busy());
+}
+
+#[test]
+fn device_errors_end_the_measurement() {
+    let sensor = mock::sensor(ReadyMode);
+    let mut poller = sensor.range_poller();
+    let mut i2c = sensor.com;
+    i2c.set(0x04F, 0b01_000_000);
+    // Start, check, then clear the error interrupt
+    assert_eq!(
+        poll_to_end(&mut poller, &mut i2c),
+        (Err(Error::LaserSafetyError), 3)
+    );
+    assert!(!poller.is_busy());
+    assert_eq!(i2c.writes.last(), Some(&(0x015, 0b100)));
+    assert_eq!(i2c.registers[0x04F], 0);
+}
+
+#[test]
+fn bus_errors_repeat_the_step() {
+    let sensor = mock::sensor(ReadyMode);
+    let mut poller = sensor.range_poller();
+    let mut i2c = sensor.com;
+    i2c.set(0x04F, 0b00_000_100);
+    i2c.set(0x062, 70);
+    assert_eq!(poller.poll(&mut i2c), Poll::Pending);
+    i2c.bus_failures = 1;
+    assert_eq!(
+        poller.poll(&mut i2c),
+        Poll::Ready(Err(Error::BusError(ErrorKind::Bus)))
+    );
+    assert!(poller.is_busy());
+    let (result, calls) = poll_to_end(&mut poller, &mut i2c);
+    assert_eq!(result, Ok(Sample::Range(Range::Mm(Millimeters(70)))));
+    assert_eq!(calls, 3);
+    // Started once
+    assert_eq!(
+        i2c.writes.iter().filter(|w| **w == (0x018, 0b01)).count(),
+        1
+    );
+}
@@ -0,0 +1,260 @@
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    config::RangeInterruptMode, error::Error, mode::AllowReadMeasurement, units::Millimeters,
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod proximity_tests;
+
+/// Config of a [ProximityDetector].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct ProximityConfig {
+    enter_mm: Millimeters,
+    exit_mm: Millimeters,
+    debounce: u8,
+    timeout_ms: Option<u32>,
+    window_interrupts: bool,
+}
+
+impl ProximityConfig {
+    /// Create new proximity config. A target arrives when it is at `enter_mm` or closer,
+    /// and departs when it is further than `exit_mm`, or no target is detected.
+    ///
+    /// `exit_mm` must not be less than `enter_mm`, the difference is the hysteresis that
+    /// keeps a target at the edge from toggling the state.
+    pub fn new(enter_mm: Millimeters, exit_mm: Millimeters) -> Result<Self, Error<()>> {
+        if exit_mm < enter_mm {
+            return Err(Error::InvalidConfigurationValue(exit_mm.0));
+        }
+        Ok(ProximityConfig {
+            enter_mm,
+            exit_mm,
+            debounce: 3,
+            timeout_ms: None,
+            window_interrupts: false,
+        })
+    }
+
+    /// Set the number of consecutive samples that must be past the enter or exit
+    /// distance before the state changes.
+    ///
+    /// Min = 1; Default = 3;
+    pub fn set_debounce(&mut self, samples: u8) -> Result<(), Error<()>> {
+        if samples < 1 {
+            return Err(Error::InvalidConfigurationValue(samples as u16));
+        }
+        self.debounce = samples;
+        Ok(())
+    }
+
+    /// Set the time in ms after arriving that a target is reported as
+    /// [ProximityEvent::TimedOut], e.g. an object left in front of the sensor.
+    ///
+    /// Default = None, no timeout;
+    pub fn set_timeout_ms(&mut self, timeout_ms: Option<u32>) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Set whether [`ProximityDetector::update()`] programs the range interrupt
+    /// with [RangeInterruptMode::OutOfWindow] thresholds, so the interrupt only fires
+    /// for samples that can change the state.
+    ///
+    /// Samples that do not fire the interrupt are not reported as ready, so only
+    /// call [`ProximityDetector::update()`] once the interrupt fired, or to check the
+    /// timeout. The range interrupt mode in the [Config](crate::Config) is overwritten.
+    ///
+    /// Default = false;
+    pub fn set_window_interrupts(&mut self, window_interrupts: bool) {
+        self.window_interrupts = window_interrupts;
+    }
+}
+
+/// Whether a target is in proximity of the sensor.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum ProximityState {
+    /// No target within the enter distance.
+    Absent,
+    /// A target arrived and has not departed yet.
+    Present,
+}
+
+/// State changes reported by [ProximityDetector].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum ProximityEvent {
+    /// A target came within the enter distance.
+    Arrived,
+    /// The target went beyond the exit distance or is no longer detected.
+    Departed,
+    /// The target has been present for longer than the timeout.
+    /// Reported once, the target is still present until it departs.
+    TimedOut,
+}
+
+/// Range interrupt programmed by the detector.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+enum Arming {
+    NewSampleReady,
+    Enter,
+    Exit,
+}
+
+/// Presence detection state machine with hysteresis, debounce and timeout.
+///
+/// Samples can be fed from any source with [`process()`](ProximityDetector::process),
+/// or read from the driver with [`update()`](ProximityDetector::update).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct ProximityDetector {
+    config: ProximityConfig,
+    state: ProximityState,
+    pending: u8,
+    present_since_ms: u32,
+    timed_out: bool,
+    /// What was programmed, and with which range scaler.
+    armed: Option<(Arming, u8)>,
+}
+
+impl ProximityDetector {
+    /// Create a new detector, starting with no target present.
+    pub fn new(config: ProximityConfig) -> Self {
+        ProximityDetector {
+            config,
+            state: ProximityState::Absent,
+            pending: 0,
+            present_since_ms: 0,
+            timed_out: false,
+            armed: None,
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> ProximityState {
+        self.state
+    }
+
+    /// Adds a range sample measured at `timestamp_ms`, from any monotonic clock.
+    /// Returns the event if the state changed or the timeout passed.
+    pub fn process(&mut self, timestamp_ms: u32, range: Range) -> Option<ProximityEvent> {
+        let crossing = match (self.state, range) {
+            (ProximityState::Absent, Range::Mm(mm)) => mm <= self.config.enter_mm,
+            (ProximityState::Absent, _) => false,
+            (ProximityState::Present, Range::Mm(mm)) => mm > self.config.exit_mm,
+            (ProximityState::Present, _) => true,
+        };
+        if !crossing {
+            self.pending = 0;
+            return self.check_timeout(timestamp_ms);
+        }
+        self.pending += 1;
+        if self.pending < self.config.debounce {
+            return self.check_timeout(timestamp_ms);
+        }
+        self.pending = 0;
+        match self.state {
+            ProximityState::Absent => {
+                self.state = ProximityState::Present;
+                self.present_since_ms = timestamp_ms;
+                self.timed_out = false;
+                Some(ProximityEvent::Arrived)
+            }
+            ProximityState::Present => {
+                self.state = ProximityState::Absent;
+                Some(ProximityEvent::Departed)
+            }
+        }
+    }
+
+    /// Returns [ProximityEvent::TimedOut] once if a target has been present for longer
+    /// than the timeout at `timestamp_ms`.
+    pub fn check_timeout(&mut self, timestamp_ms: u32) -> Option<ProximityEvent> {
+        let timeout_ms = self.config.timeout_ms?;
+        if self.state == ProximityState::Present &&
+            !self.timed_out &&
+            timestamp_ms.wrapping_sub(self.present_since_ms) > timeout_ms
+        {
+            self.timed_out = true;
+            return Some(ProximityEvent::TimedOut);
+        }
+        None
+    }
+
+    /// Reads a new range sample from the driver with [`VL6180X::read_range()`] and
+    /// processes it. If no new sample is ready only the timeout is checked.
+    ///
+    /// With [window interrupts](ProximityConfig::set_window_interrupts) enabled the range
+    /// interrupt is reprogrammed when the state changes.
+    pub async fn update<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+        timestamp_ms: u32,
+    ) -> Result<Option<ProximityEvent>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement,
+    {
+        let event = match vl6180x.read_range().await {
+            Ok(range) => self.process(timestamp_ms, range),
+            Err(Error::ResultNotReady) => self.check_timeout(timestamp_ms),
+            Err(e) => return Err(e),
+        };
+        if self.config.window_interrupts {
+            self.arm_interrupts(vl6180x).await?;
+        }
+        Ok(event)
+    }
+
+    /// Programs the range interrupt for the current state, if it or the range scaler
+    /// changed since it was last programmed. Called by [`update()`](ProximityDetector::update) when
+    /// [window interrupts](ProximityConfig::set_window_interrupts) are enabled, and can be
+    /// called before starting the measurements.
+    ///
+    /// Fires when a target comes within the enter distance while absent, or goes beyond
+    /// the exit distance while present. While a state change is being debounced, fires
+    /// for every new sample.
+    pub async fn arm_interrupts<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        let arming = match (self.pending, self.state) {
+            (0, ProximityState::Absent) => Arming::Enter,
+            (0, ProximityState::Present) => Arming::Exit,
+            _ => Arming::NewSampleReady,
+        };
+        let scaler = vl6180x.config.range_scaling;
+        if self.armed == Some((arming, scaler)) {
+            return Ok(());
+        }
+        let scaling = scaler.max(1) as u16;
+        let (interrupt_mode, low, high) = match arming {
+            Arming::NewSampleReady => (RangeInterruptMode::NewSampleReady, 0, 0xFF),
+            // Fires when the value is below the low threshold
+            Arming::Enter => (
+                RangeInterruptMode::OutOfWindow,
+                (self.config.enter_mm.0 / scaling + 1).min(0xFF) as u8,
+                0xFF,
+            ),
+            // Fires when the value is above the high threshold, or there is no target
+            Arming::Exit => (
+                RangeInterruptMode::OutOfWindow,
+                0,
+                (self.config.exit_mm.0 / scaling).min(0xFF) as u8,
+            ),
+        };
+        // Forget the arming first, so it is programmed again if writing fails
+        self.armed = None;
+        vl6180x
+            .set_range_interrupt_direct(interrupt_mode, low, high)
+            .await?;
+        self.armed = Some((arming, scaler));
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::{mock, mode::RangeContinuousMode};
+
+fn detector(debounce: u8) -> ProximityDetector {
+    let mut config = ProximityConfig::new(Millimeters(50), Millimeters(80)).unwrap();
+    config.set_debounce(debounce).unwrap();
+    ProximityDetector::new(config)
+}
+
+#[test]
+fn config_validation() {
+    assert_eq!(
+        ProximityConfig::new(Millimeters(80), Millimeters(50)),
+        Err(Error::InvalidConfigurationValue(50))
+    );
+    let mut config = ProximityConfig::new(Millimeters(50), Millimeters(50)).unwrap();
+    assert_eq!(
+        config.set_debounce(0),
+        Err(Error::InvalidConfigurationValue(0))
+    );
+}
+
+#[test]
+fn hysteresis_between_enter_and_exit() {
+    let mut detector = detector(1);
+    assert_eq!(detector.process(0, Range::Mm(Millimeters(60))), None);
+    assert_eq!(
+        detector.process(1, Range::Mm(Millimeters(50))),
+        Some(ProximityEvent::Arrived)
+    );
+    assert_eq!(detector.state(), ProximityState::Present);
+    assert_eq!(detector.process(2, Range::Mm(Millimeters(70))), None);
+    assert_eq!(detector.process(3, Range::Mm(Millimeters(80))), None);
+    assert_eq!(
+        detector.process(4, Range::Mm(Millimeters(81))),
+        Some(ProximityEvent::Departed)
+    );
+    assert_eq!(detector.state(), ProximityState::Absent);
+    assert_eq!(detector.process(5, Range::Mm(Millimeters(70))), None);
+}
+
+#[test]
+fn no_target_departs() {
+    let mut detector = detector(1);
+    assert_eq!(detector.process(0, Range::NoTarget), None);
+    detector.process(1, Range::Mm(Millimeters(10)));
+    assert_eq!(
+        detector.process(2, Range::OutOfRange),
+        Some(ProximityEvent::Departed)
+    );
+    detector.process(3, Range::Mm(Millimeters(10)));
+    assert_eq!(
+        detector.process(4, Range::NoTarget),
+        Some(ProximityEvent::Departed)
+    );
+}
+
+#[test]
+fn debounce_needs_consecutive_samples() {
+    let mut detector = detector(3);
+    assert_eq!(detector.process(0, Range::Mm(Millimeters(10))), None);
+    assert_eq!(detector.process(1, Range::Mm(Millimeters(10))), None);
+    // Chatter resets the count
+    assert_eq!(detector.process(2, Range::Mm(Millimeters(100))), None);
+    assert_eq!(detector.process(3, Range::Mm(Millimeters(10))), None);
+    assert_eq!(detector.process(4, Range::Mm(Millimeters(10))), None);
+    assert_eq!(
+        detector.process(5, Range::Mm(Millimeters(10))),
+        Some(ProximityEvent::Arrived)
+    );
+}
+
+#[test]
+fn timeout_is_reported_once() {
+    let mut config = ProximityConfig::new(Millimeters(50), Millimeters(80)).unwrap();
+    config.set_debounce(1).unwrap();
+    config.set_timeout_ms(Some(1_000));
+    let mut detector = ProximityDetector::new(config);
+    assert_eq!(detector.check_timeout(5_000), None);
+    detector.process(100, Range::Mm(Millimeters(10)));
+    assert_eq!(detector.process(1_100, Range::Mm(Millimeters(10))), None);
+    assert_eq!(
+        detector.check_timeout(1_101),
+        Some(ProximityEvent::TimedOut)
+    );
+    assert_eq!(detector.process(1_200, Range::Mm(Millimeters(10))), None);
+    assert_eq!(detector.state(), ProximityState::Present);
+    assert_eq!(
+        detector.process(1_300, Range::NoTarget),
+        Some(ProximityEvent::Departed)
+    );
+}
+
+fn sensor_with_range(range_mm: u8) -> VL6180X<RangeContinuousMode, mock::MockI2c> {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    set_range(&mut sensor, range_mm);
+    sensor
+}
+
+fn set_range(sensor: &mut VL6180X<RangeContinuousMode, mock::MockI2c>, range_mm: u8) {
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x062, range_mm);
+}
+
+#[test]
+fn update_reads_sample_from_driver() {
+    let mut sensor = sensor_with_range(20);
+    let mut detector = detector(1);
+    assert_eq!(
+        block_on(detector.update(&mut sensor, 0)),
+        Ok(Some(ProximityEvent::Arrived))
+    );
+    // No new sample
+    assert_eq!(block_on(detector.update(&mut sensor, 1)), Ok(None));
+    assert!(sensor.com.writes.iter().all(|(reg, _)| *reg == 0x015));
+}
+
+#[test]
+fn update_programs_window_interrupts() {
+    let mut config = ProximityConfig::new(Millimeters(50), Millimeters(80)).unwrap();
+    config.set_debounce(2).unwrap();
+    config.set_window_interrupts(true);
+    let mut detector = ProximityDetector::new(config);
+    let mut sensor = sensor_with_range(100);
+
+    block_on(detector.update(&mut sensor, 0)).unwrap();
+    // Waiting for arrival: interrupt below 51mm
+    assert_eq!(
+        sensor.com.writes,
+        [
+            (0x015, 0b001),
+            (0x017, 1),
+            (0x014, 0b100_011),
+            (0x011, 0b0011_0000),
+            (0x019, 0xFF),
+            (0x01A, 51),
+            (0x017, 0)
+        ]
+    );
+    assert_eq!(sensor.config.range_low_interrupt_threshold, 51);
+
+    // Debouncing the arrival: every sample
+    sensor.com.writes.clear();
+    set_range(&mut sensor, 30);
+    block_on(detector.update(&mut sensor, 1)).unwrap();
+    assert!(sensor.com.was_written(0x014, 0b100_100));
+
+    // Present: interrupt above 80mm or no target
+    sensor.com.writes.clear();
+    set_range(&mut sensor, 30);
+    assert_eq!(
+        block_on(detector.update(&mut sensor, 2)),
+        Ok(Some(ProximityEvent::Arrived))
+    );
+    assert!(sensor.com.was_written(0x014, 0b100_011));
+    assert!(sensor.com.was_written(0x019, 80));
+    assert!(sensor.com.was_written(0x01A, 0));
+
+    // Nothing to reprogram
+    sensor.com.writes.clear();
+    block_on(detector.update(&mut sensor, 3)).unwrap();
+    assert!(sensor.com.writes.is_empty());
+}
+
+#[test]
+fn window_thresholds_follow_range_scaling() {
+    let mut config = ProximityConfig::new(Millimeters(300), Millimeters(600)).unwrap();
+    config.set_window_interrupts(true);
+    let mut detector = ProximityDetector::new(config);
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    sensor.config.range_scaling = 3;
+    block_on(detector.arm_interrupts(&mut sensor)).unwrap();
+    assert!(sensor.com.was_written(0x01A, 101));
+}
+
+#[test]
+fn window_is_reprogrammed_after_scaler_change() {
+    let mut config = ProximityConfig::new(Millimeters(201), Millimeters(250)).unwrap();
+    config.set_window_interrupts(true);
+    let mut detector = ProximityDetector::new(config);
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    block_on(detector.arm_interrupts(&mut sensor)).unwrap();
+    assert!(sensor.com.was_written(0x01A, 202));
+    // Rescaled to 202 / 3 = 67
+    block_on(sensor.set_range_result_scaler(3)).unwrap();
+    block_on(detector.arm_interrupts(&mut sensor)).unwrap();
+    assert_eq!(sensor.config.range_low_interrupt_threshold, 68);
+}
@@ -2,26 +2,42 @@ use core::convert::TryFrom;
 
 use embedded_hal_async::i2c::I2c;
 
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{Mcps, MilliLux, Millimeters, RawAmbientCount};
 use crate::{
     error::Error,
     register::{
-        self, AmbientStatusErrorCode, RangeStatusErrorCode, Register16Bit, Register8Bit,
-        ResultInterruptStatusGpioCode,
+        self, AmbientStatusErrorCode, InterruptErrorCode, InterruptStatus,
+        RangeStatusErrorCode, Register16Bit, Register8Bit,
     },
-    VL6180X,
+    Range, VL6180X,
 };
 
+#[cfg(test)]
+mod read_measurements_tests;
+
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
 where
     I2C: I2c<Error = E>,
 {
-    pub(crate) async fn read_range_mm_blocking_direct(&mut self) -> Result<u16, Error<E>> {
+    /// Reads the interrupt status, returning the device error reported in it as an error.
+    pub(crate) async fn read_interrupt_status_checked(
+        &mut self,
+    ) -> Result<InterruptStatus, Error<E>> {
+        let status = self.read_interrupt_status_direct().await?;
+        match status.error {
+            InterruptErrorCode::NoError => Ok(status),
+            InterruptErrorCode::LaserSafetyError => Err(Error::LaserSafetyError),
+            InterruptErrorCode::PllError => Err(Error::PllError),
+        }
+    }
+
+    pub(crate) async fn read_range_mm_blocking_direct(
+        &mut self,
+    ) -> Result<Millimeters, Error<E>> {
         let mut c = 0;
-        while ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoRangeEvents,
-            self.read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-                .await?,
-        ) {
+        while !self.read_interrupt_status_checked().await?.range_ready() {
             c += 1;
             if c == self.config.poll_max_loop {
                 return Err(Error::Timeout);
@@ -31,46 +47,77 @@ where
         self.get_range_val_and_status().await
     }
 
-    pub(crate) async fn read_range_mm_direct(&mut self) -> Result<u16, Error<E>> {
-        let interrupt_status = self
-            .read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-            .await?;
-        if ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoRangeEvents,
-            interrupt_status,
-        ) {
+    pub(crate) async fn read_range_mm_direct(&mut self) -> Result<Millimeters, Error<E>> {
+        if !self.read_interrupt_status_checked().await?.range_ready() {
             return Err(Error::ResultNotReady);
         }
         self.get_range_val_and_status().await
     }
 
-    async fn get_range_val_and_status(&mut self) -> Result<u16, Error<E>> {
+    pub(crate) async fn read_range_blocking_direct(&mut self) -> Result<Range, Error<E>> {
+        let mut c = 0;
+        while !self.read_interrupt_status_checked().await?.range_ready() {
+            c += 1;
+            if c == self.config.poll_max_loop {
+                return Err(Error::Timeout);
+            }
+        }
+
+        self.get_range().await
+    }
+
+    pub(crate) async fn read_range_direct(&mut self) -> Result<Range, Error<E>> {
+        if !self.read_interrupt_status_checked().await?.range_ready() {
+            return Err(Error::ResultNotReady);
+        }
+        self.get_range().await
+    }
+
+    async fn get_range_val_and_status(&mut self) -> Result<Millimeters, Error<E>> {
+        self.get_range_status_and_val()
+            .await?
+            .map_err(Error::RangeStatusError)
+    }
+
+    async fn get_range(&mut self) -> Result<Range, Error<E>> {
+        match self.get_range_status_and_val().await? {
+            Ok(range) => Ok(Range::Mm(range)),
+            Err(code) => Range::from_status_error(code).ok_or(Error::RangeStatusError(code)),
+        }
+    }
+
+    /// Reads the range status and clears the range interrupt.
+    /// Only reads the range value if the status has no error, otherwise returns the
+    /// range status error as the inner error.
+    async fn get_range_status_and_val(
+        &mut self,
+    ) -> Result<Result<Millimeters, RangeStatusErrorCode>, Error<E>> {
         let status = self
             .read_named_register(Register8Bit::RESULT__RANGE_STATUS)
             .await?;
+        let error = RangeStatusErrorCode::try_from(status);
+        let raw_range = if error == Ok(RangeStatusErrorCode::NoError) {
+            self.read_named_register(Register8Bit::RESULT__RANGE_VAL)
+                .await?
+        } else {
+            0
+        };
+        // Cleared last, so a read that is dropped leaves the result to the next one
         self.clear_range_interrupt_direct().await?;
-        let error = RangeStatusErrorCode::try_from(status)
-            .map_err(|_| Error::UnknownRegisterCode(status))?;
-        if error != RangeStatusErrorCode::NoError {
-            return Err(Error::RangeStatusError(error));
+        match error.map_err(|_| Error::UnknownRegisterCode(status))? {
+            RangeStatusErrorCode::NoError => Ok(Ok(self.convert_raw_range_to_mm(raw_range))),
+            error => Ok(Err(error)),
         }
-        let raw_range = self
-            .read_named_register(Register8Bit::RESULT__RANGE_VAL)
-            .await?;
-        Ok(self.convert_raw_range_to_mm(raw_range))
     }
 
-    fn convert_raw_range_to_mm(&self, raw_range: u8) -> u16 {
-        self.config.range_scaling as u16 * raw_range as u16
+    fn convert_raw_range_to_mm(&self, raw_range: u8) -> Millimeters {
+        Millimeters(self.config.range_scaling as u16 * raw_range as u16)
     }
 
-    pub(crate) async fn read_ambient_lux_blocking_direct(&mut self) -> Result<f32, Error<E>> {
+    #[cfg(feature = "float")]
+    pub(crate) async fn read_ambient_lux_blocking_direct(&mut self) -> Result<Lux, Error<E>> {
         let mut c = 0;
-        while ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoAmbientEvents,
-            self.read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-                .await?,
-        ) {
+        while !self.read_interrupt_status_checked().await?.ambient_ready() {
             c += 1;
             if c == self.config.poll_max_loop {
                 return Err(Error::Timeout);
@@ -80,25 +127,34 @@ where
         Ok(self.convert_raw_ambient_to_lux(raw_ambient))
     }
 
-    pub(crate) async fn read_ambient_lux_direct(&mut self) -> Result<f32, Error<E>> {
-        if ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoAmbientEvents,
-            self.read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-                .await?,
-        ) {
+    #[cfg(feature = "float")]
+    pub(crate) async fn read_ambient_lux_direct(&mut self) -> Result<Lux, Error<E>> {
+        if !self.read_interrupt_status_checked().await?.ambient_ready() {
             return Err(Error::ResultNotReady);
         }
         let raw_ambient = self.get_ambient_val_and_status().await?;
         Ok(self.convert_raw_ambient_to_lux(raw_ambient))
     }
 
-    pub(crate) async fn read_ambient_blocking_direct(&mut self) -> Result<u16, Error<E>> {
+    pub(crate) async fn read_ambient_milli_lux_blocking_direct(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        let raw_ambient = self.read_ambient_blocking_direct().await?;
+        Ok(self.convert_raw_ambient_to_milli_lux(raw_ambient))
+    }
+
+    pub(crate) async fn read_ambient_milli_lux_direct(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        let raw_ambient = self.read_ambient_direct().await?;
+        Ok(self.convert_raw_ambient_to_milli_lux(raw_ambient))
+    }
+
+    pub(crate) async fn read_ambient_blocking_direct(
+        &mut self,
+    ) -> Result<RawAmbientCount, Error<E>> {
         let mut c = 0;
-        while ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoAmbientEvents,
-            self.read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-                .await?,
-        ) {
+        while !self.read_interrupt_status_checked().await?.ambient_ready() {
             c += 1;
             if c == self.config.poll_max_loop {
                 return Err(Error::Timeout);
@@ -107,42 +163,89 @@ where
         self.get_ambient_val_and_status().await
     }
 
-    pub(crate) async fn read_ambient_direct(&mut self) -> Result<u16, Error<E>> {
-        if ResultInterruptStatusGpioCode::has_status(
-            ResultInterruptStatusGpioCode::NoAmbientEvents,
-            self.read_named_register(Register8Bit::RESULT__INTERRUPT_STATUS_GPIO)
-                .await?,
-        ) {
+    pub(crate) async fn read_ambient_direct(&mut self) -> Result<RawAmbientCount, Error<E>> {
+        if !self.read_interrupt_status_checked().await?.ambient_ready() {
             return Err(Error::ResultNotReady);
         }
         self.get_ambient_val_and_status().await
     }
 
-    async fn get_ambient_val_and_status(&mut self) -> Result<u16, Error<E>> {
+    pub(crate) async fn read_range_return_rate_direct(&mut self) -> Result<Mcps, Error<E>> {
+        Ok(Mcps(
+            self.read_named_register_16bit(Register16Bit::RESULT__RANGE_RETURN_RATE)
+                .await?,
+        ))
+    }
+
+    async fn get_ambient_val_and_status(&mut self) -> Result<RawAmbientCount, Error<E>> {
         let status = self
             .read_named_register(Register8Bit::RESULT__ALS_STATUS)
             .await?;
+        let error = AmbientStatusErrorCode::try_from(status);
+        let raw_ambient = if error == Ok(AmbientStatusErrorCode::NoError) {
+            self.read_named_register_16bit(Register16Bit::RESULT__ALS_VAL)
+                .await?
+        } else {
+            0
+        };
+        // Cleared last, so a read that is dropped leaves the result to the next one
         self.clear_ambient_interrupt_direct().await?;
-        let error = AmbientStatusErrorCode::try_from(status)
-            .map_err(|_| Error::UnknownRegisterCode(status))?;
-        if error != AmbientStatusErrorCode::NoError {
-            return Err(Error::AmbientStatusError(error));
+        match error.map_err(|_| Error::UnknownRegisterCode(status))? {
+            AmbientStatusErrorCode::NoError => Ok(RawAmbientCount(raw_ambient)),
+            error => Err(Error::AmbientStatusError(error)),
         }
-        let raw_ambient = self
-            .read_named_register_16bit(Register16Bit::RESULT__ALS_VAL)
-            .await?;
-        Ok(raw_ambient)
     }
 
-    fn convert_raw_ambient_to_lux(&self, raw_ambient: u16) -> f32 {
-        let analogue_gain = register::AMBIENT_ANALOGUE_GAIN_VALUE
-            [self.config.ambient_analogue_gain_level as usize];
+    #[cfg(feature = "float")]
+    fn convert_raw_ambient_to_lux(&self, raw_ambient: RawAmbientCount) -> Lux {
+        convert_raw_ambient_to_lux(
+            raw_ambient,
+            self.config.ambient_analogue_gain_level,
+            self.config.ambient_integration_period,
+        )
+    }
 
-        let integration_period = self.config.ambient_integration_period;
+    fn convert_raw_ambient_to_milli_lux(&self, raw_ambient: RawAmbientCount) -> MilliLux {
+        convert_raw_ambient_to_milli_lux(
+            raw_ambient,
+            self.config.ambient_analogue_gain_level,
+            self.config.ambient_integration_period,
+        )
+    }
+}
 
-        const LUX_RESOLUTION_FACTOR: f32 = 0.32_f32;
+/// Converts a raw ambient light count measured with the given analogue gain level
+/// and integration period to lux.
+#[cfg(feature = "float")]
+pub(crate) fn convert_raw_ambient_to_lux(
+    raw_ambient: RawAmbientCount,
+    analogue_gain_level: u8,
+    integration_period: u16,
+) -> Lux {
+    let analogue_gain = register::AMBIENT_ANALOGUE_GAIN_VALUE[analogue_gain_level as usize];
 
-        (LUX_RESOLUTION_FACTOR * 100.0 / analogue_gain) *
-            (raw_ambient as f32 / integration_period as f32)
-    }
+    const LUX_RESOLUTION_FACTOR: f32 = 0.32_f32;
+
+    Lux((LUX_RESOLUTION_FACTOR * 100.0 / analogue_gain) *
+        (raw_ambient.0 as f32 / integration_period as f32))
+}
+
+/// Converts a raw ambient light count measured with the given analogue gain level
+/// and integration period to milli-lux, rounded to the nearest, using integer maths only.
+///
+/// lux = 0.32 * 100 / gain * raw / integration_period, with the gain scaled by 1000.
+/// The largest result (gain 1.01, 1ms, full count) still fits a `u32`.
+pub(crate) fn convert_raw_ambient_to_milli_lux(
+    raw_ambient: RawAmbientCount,
+    analogue_gain_level: u8,
+    integration_period: u16,
+) -> MilliLux {
+    let analogue_gain_milli =
+        register::AMBIENT_ANALOGUE_GAIN_MILLI[analogue_gain_level as usize] as u64;
+
+    const MILLI_LUX_RESOLUTION_FACTOR: u64 = 32 * 1000 * 1000;
+
+    let numerator = MILLI_LUX_RESOLUTION_FACTOR * raw_ambient.0 as u64;
+    let denominator = analogue_gain_milli * integration_period as u64;
+    MilliLux(((numerator + denominator / 2) / denominator) as u32)
 }
@@ -0,0 +1,211 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::{mock, mode::ReadyMode, register::InterruptEventCode};
+
+#[test]
+fn read_range_reports_laser_safety_error() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b01_000_100);
+    assert_eq!(
+        block_on(sensor.read_range_mm()),
+        Err(Error::LaserSafetyError)
+    );
+}
+
+#[test]
+fn blocking_read_reports_pll_error_without_waiting_for_timeout() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b10_000_000);
+    assert_eq!(
+        block_on(sensor.read_ambient_blocking()),
+        Err(Error::PllError)
+    );
+    assert_eq!(
+        block_on(sensor.read_range_mm_blocking()),
+        Err(Error::PllError)
+    );
+}
+
+#[test]
+fn unknown_device_error_code() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b11_100_000);
+    assert_eq!(
+        block_on(sensor.read_ambient()),
+        Err(Error::UnknownRegisterCode(0b11_100_000))
+    );
+}
+
+#[test]
+fn read_device_errors_clears_the_error() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b01_000_100);
+    assert_eq!(
+        block_on(sensor.read_device_errors()),
+        Ok(InterruptErrorCode::LaserSafetyError)
+    );
+    assert!(sensor.com.was_written(0x015, 0b100));
+    assert_eq!(block_on(sensor.read_range_mm()), Ok(Millimeters(0)));
+}
+
+#[test]
+fn read_device_errors_clears_an_unknown_error() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b11_000_000);
+    assert_eq!(
+        block_on(sensor.read_device_errors()),
+        Err(Error::UnknownRegisterCode(0b11_000_000))
+    );
+    assert!(sensor.com.was_written(0x015, 0b100));
+    assert_eq!(sensor.com.registers[0x04F], 0);
+}
+
+#[test]
+fn read_device_errors_without_error_does_not_clear() {
+    let mut sensor = mock::sensor(ReadyMode);
+    assert_eq!(
+        block_on(sensor.read_device_errors()),
+        Ok(InterruptErrorCode::NoError)
+    );
+    assert!(sensor.com.writes.is_empty());
+}
+
+#[test]
+fn read_interrupt_status_is_decoded() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b00_100_011);
+    let status = block_on(sensor.read_interrupt_status()).unwrap();
+    assert_eq!(status.range_event, InterruptEventCode::OutOfWindow);
+    assert_eq!(status.ambient_event, InterruptEventCode::NewSampleReady);
+    assert_eq!(status.error, InterruptErrorCode::NoError);
+}
+
+/// Sensor with a range sample ready that has the given RESULT__RANGE_STATUS error code.
+fn sensor_with_range_status(code: u8) -> VL6180X<ReadyMode, mock::MockI2c> {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, code << 4 | 0x01);
+    sensor.com.set(0x062, 123);
+    sensor
+}
+
+#[test]
+fn read_range_returns_distance() {
+    let mut sensor = sensor_with_range_status(0b0000);
+    assert_eq!(
+        block_on(sensor.read_range()),
+        Ok(Range::Mm(Millimeters(123)))
+    );
+    assert!(sensor.com.was_written(0x015, 0b001));
+}
+
+#[test]
+fn read_range_no_target_is_not_an_error() {
+    let mut sensor = sensor_with_range_status(0b0111);
+    assert_eq!(block_on(sensor.read_range_blocking()), Ok(Range::NoTarget));
+    assert!(sensor.com.was_written(0x015, 0b001));
+    // The old API still reports it as an error
+    let mut sensor = sensor_with_range_status(0b0111);
+    assert_eq!(
+        block_on(sensor.read_range_mm()),
+        Err(Error::RangeStatusError(
+            RangeStatusErrorCode::MaxConvergence
+        ))
+    );
+}
+
+#[test]
+fn read_range_out_of_range() {
+    let mut sensor = sensor_with_range_status(0b1111);
+    assert_eq!(block_on(sensor.read_range()), Ok(Range::OutOfRange));
+}
+
+#[test]
+fn read_range_keeps_device_faults_as_errors() {
+    let mut sensor = sensor_with_range_status(0b0011);
+    assert_eq!(
+        block_on(sensor.read_range()),
+        Err(Error::RangeStatusError(RangeStatusErrorCode::VcselWatchdog))
+    );
+}
+
+#[test]
+fn read_range_not_ready() {
+    let mut sensor = sensor_with_range_status(0b0000);
+    sensor.com.set(0x04F, 0);
+    assert_eq!(block_on(sensor.read_range()), Err(Error::ResultNotReady));
+}
+
+#[test]
+fn poll_range_single_blocking_starts_measurement() {
+    let mut sensor = sensor_with_range_status(0b0110);
+    assert_eq!(
+        block_on(sensor.poll_range_single_blocking()),
+        Ok(Range::NoTarget)
+    );
+    assert_eq!(sensor.com.writes.first(), Some(&(0x018, 0b01)));
+}
+
+#[test]
+fn read_range_return_rate() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x066, 0x02);
+    sensor.com.set(0x067, 0x80);
+    assert_eq!(block_on(sensor.read_range_return_rate()), Ok(Mcps(5 * 128)));
+}
+
+#[test]
+fn milli_lux_conversion_without_overflow() {
+    assert_eq!(
+        convert_raw_ambient_to_milli_lux(RawAmbientCount(0), 0, 100),
+        MilliLux(0)
+    );
+    assert_eq!(
+        convert_raw_ambient_to_milli_lux(RawAmbientCount(100), 0, 100),
+        MilliLux(31_683)
+    );
+    assert_eq!(
+        convert_raw_ambient_to_milli_lux(RawAmbientCount(100), 7, 100),
+        MilliLux(800)
+    );
+    assert_eq!(
+        convert_raw_ambient_to_milli_lux(RawAmbientCount(1), 7, 256),
+        MilliLux(3)
+    );
+    assert_eq!(
+        convert_raw_ambient_to_milli_lux(RawAmbientCount(u16::MAX), 0, 1),
+        MilliLux(2_076_356_436)
+    );
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn milli_lux_matches_lux() {
+    for gain_level in 0..8 {
+        for &(raw, integration_period) in &[(1, 1), (777, 100), (40_000, 50), (65_535, 256)] {
+            let raw = RawAmbientCount(raw);
+            let Lux(lux) = convert_raw_ambient_to_lux(raw, gain_level, integration_period);
+            let MilliLux(milli_lux) =
+                convert_raw_ambient_to_milli_lux(raw, gain_level, integration_period);
+            assert!((milli_lux as f32 - lux * 1000.0).abs() <= 0.5 + lux * 1e-3);
+        }
+    }
+}
+
+#[test]
+fn poll_ambient_milli_lux_single_blocking() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b00_100_000);
+    sensor.com.set(0x050, 0x03);
+    sensor.com.set(0x051, 0xE8);
+    assert_eq!(
+        block_on(sensor.poll_ambient_milli_lux_single_blocking()),
+        Ok(MilliLux(316_832))
+    );
+    assert_eq!(sensor.com.writes.first(), Some(&(0x038, 0b01)));
+    assert_eq!(
+        block_on(sensor.read_ambient_milli_lux()),
+        Err(Error::ResultNotReady)
+    );
+}
@@ -150,9 +150,16 @@ pub enum InterleavedModeEnableCode {
     Disable = 0,
 }
 
+/// Bit 0 of RESULT__RANGE_STATUS and RESULT__ALS_STATUS.
+///
+/// Set once the measurement in progress has finished and the device is ready
+/// to accept a new start command. See VL6180X datasheet section 6.2.37 and 6.2.38
+pub const RESULT_DEVICE_READY: u8 = 0b0000_0001;
+
 /// Result interrupt status codes
 ///
-/// Use [`has_status()`](ResultInterruptStatusGpioCode::has_status) to check if the result returned from [`read_interrupt_status()`](crate::VL6180X::read_interrupt_status)
+/// Use [`has_status()`](ResultInterruptStatusGpioCode::has_status) to check a raw status value,
+/// [`read_interrupt_status()`](crate::VL6180X::read_interrupt_status) returns it decoded as an [InterruptStatus]
 /// Register: RESULT__INTERRUPT_STATUS_GPIO
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(::defmt::Format))]
@@ -187,23 +194,117 @@ pub enum ResultInterruptStatusGpioCode {
     NewSampleReadyRangeEvent = 0b00_000_100,
 }
 
+pub(crate) const INTERRUPT_ERROR_MASK: u8 = 0b11_000_000;
+pub(crate) const INTERRUPT_AMBIENT_MASK: u8 = 0b00_111_000;
+pub(crate) const INTERRUPT_RANGE_MASK: u8 = 0b00_000_111;
+
 impl ResultInterruptStatusGpioCode {
     /// Returns whether there is the specific event reported within the ResultInterruptStatusGpioCode
     pub fn has_status(look_for: ResultInterruptStatusGpioCode, within: u8) -> bool {
-        const ERROR_MASK: u8 = 0b11_000_000;
-        const AMBIENT_MASK: u8 = 0b00_111_000;
-        const RANGE_MASK: u8 = 0b00_000_111;
-
         use ResultInterruptStatusGpioCode::*;
         match look_for {
-            NoError => within & ERROR_MASK == 0,
-            NoAmbientEvents => within & AMBIENT_MASK == 0,
-            NoRangeEvents => within & RANGE_MASK == 0,
+            NoError => within & INTERRUPT_ERROR_MASK == 0,
+            NoAmbientEvents => within & INTERRUPT_AMBIENT_MASK == 0,
+            NoRangeEvents => within & INTERRUPT_RANGE_MASK == 0,
             _other_status => within & _other_status as u8 != 0,
         }
     }
 }
 
+/// Threshold and new sample events reported by the interrupt status,
+/// for either range or ambient light.
+/// See VL6180X datasheet section 6.2.39 RESULT__INTERRUPT_STATUS_GPIO
+// Bits 5:3 (ambient) or 2:0 (range) of what is returned from the register
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, IntEnum, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum InterruptEventCode {
+    /// No threshold events reported
+    NoEvent = 0b000,
+    /// Low level threshold event
+    LevelLow = 0b001,
+    /// High level threshold event
+    LevelHigh = 0b010,
+    /// Out of window threshold event
+    OutOfWindow = 0b011,
+    /// New sample ready event
+    NewSampleReady = 0b100,
+}
+
+/// Decoded interrupt status of the sensor.
+/// See VL6180X datasheet section 6.2.39 RESULT__INTERRUPT_STATUS_GPIO
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct InterruptStatus {
+    /// Range measurement event
+    pub range_event: InterruptEventCode,
+    /// Ambient light measurement event
+    pub ambient_event: InterruptEventCode,
+    /// Device error
+    pub error: InterruptErrorCode,
+}
+
+impl InterruptStatus {
+    /// Returns whether a range measurement result is ready.
+    ///
+    /// The range event is reported along with a new sample, whichever
+    /// [RangeInterruptMode](crate::config::RangeInterruptMode) is configured.
+    pub fn range_ready(&self) -> bool {
+        self.range_event != InterruptEventCode::NoEvent
+    }
+
+    /// Returns whether an ambient light measurement result is ready.
+    ///
+    /// The ambient event is reported along with a new sample, whichever
+    /// [AmbientInterruptMode](crate::config::AmbientInterruptMode) is configured.
+    pub fn ambient_ready(&self) -> bool {
+        self.ambient_event != InterruptEventCode::NoEvent
+    }
+
+    /// Returns whether a device error is reported.
+    pub fn has_error(&self) -> bool {
+        self.error != InterruptErrorCode::NoError
+    }
+}
+
+impl TryFrom<u8> for InterruptStatus {
+    /// The status that could not be decoded
+    type Error = u8;
+    fn try_from(status: u8) -> Result<Self, Self::Error> {
+        Ok(InterruptStatus {
+            range_event: InterruptEventCode::from_int(status & INTERRUPT_RANGE_MASK)
+                .map_err(|_| status)?,
+            ambient_event: InterruptEventCode::from_int(
+                (status & INTERRUPT_AMBIENT_MASK) >> 3,
+            )
+            .map_err(|_| status)?,
+            error: InterruptErrorCode::try_from(status).map_err(|_| status)?,
+        })
+    }
+}
+
+/// Device errors reported by the interrupt status
+/// See VL6180X datasheet section 6.2.39 RESULT__INTERRUPT_STATUS_GPIO
+// Bits 7:6 of what is returned from the register
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, IntEnum, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum InterruptErrorCode {
+    /// No error reported
+    NoError = 0b00,
+    /// Laser safety error
+    LaserSafetyError = 0b01,
+    /// Phase Locked Loop (PLL) error (either PLL1 or PLL2)
+    PllError = 0b10,
+}
+
+impl TryFrom<u8> for InterruptErrorCode {
+    type Error = IntEnumError<Self>;
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        InterruptErrorCode::from_int(code >> 6)
+    }
+}
+
 /// Errors from performing a range measurement
 /// See VL6180X datasheet section 2.7.2 Range error codes
 /// or section 6.2.37 RESULT__RANGE_STATUS
@@ -288,5 +389,9 @@ pub const RANGE_SCALAR_CODE: [u16; 4] = [0, 253, 127, 84];
 /// See datasheet 2.10.6 for more details
 pub const AMBIENT_ANALOGUE_GAIN_CODE: [u8; 8] =
     [0x46, 0x45, 0x44, 0x43, 0x42, 0x41, 0x40, 0x47];
+#[cfg(feature = "float")]
 pub const AMBIENT_ANALOGUE_GAIN_VALUE: [f32; 8] =
     [1.01, 1.28, 1.72, 2.60, 5.21, 10.32, 20.0, 40.0];
+/// AMBIENT_ANALOGUE_GAIN_VALUE times 1000, for integer maths
+pub const AMBIENT_ANALOGUE_GAIN_MILLI: [u32; 8] =
+    [1010, 1280, 1720, 2600, 5210, 10320, 20000, 40000];
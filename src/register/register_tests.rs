@@ -97,3 +97,68 @@ fn range_status_error_code_known() {
 fn range_status_error_code_unknown() {
     assert!(RangeStatusErrorCode::try_from(0b1001_0000).is_err())
 }
+
+#[test]
+fn interrupt_error_code_from_status() {
+    assert_eq!(
+        InterruptErrorCode::try_from(0b10_000_100).unwrap(),
+        InterruptErrorCode::PllError
+    );
+    assert_eq!(
+        InterruptErrorCode::try_from(0b00_100_100).unwrap(),
+        InterruptErrorCode::NoError
+    );
+    assert!(InterruptErrorCode::try_from(0b11_000_000).is_err())
+}
+
+#[test]
+fn interrupt_status_decodes_all_fields() {
+    assert_eq!(
+        InterruptStatus::try_from(0b01_011_100),
+        Ok(InterruptStatus {
+            range_event: InterruptEventCode::NewSampleReady,
+            ambient_event: InterruptEventCode::OutOfWindow,
+            error: InterruptErrorCode::LaserSafetyError,
+        })
+    )
+}
+
+#[test]
+fn interrupt_status_ready_helpers() {
+    let status = InterruptStatus::try_from(0b00_000_001).unwrap();
+    assert!(status.range_ready());
+    assert!(!status.ambient_ready());
+    assert!(!status.has_error());
+
+    let status = InterruptStatus::try_from(0b10_100_000).unwrap();
+    assert!(!status.range_ready());
+    assert!(status.ambient_ready());
+    assert!(status.has_error());
+}
+
+#[test]
+fn interrupt_status_matches_has_status() {
+    use ResultInterruptStatusGpioCode::*;
+    for raw in 0..=0b10_100_100_u8 {
+        if let Ok(status) = InterruptStatus::try_from(raw) {
+            assert_eq!(
+                status.range_ready(),
+                !ResultInterruptStatusGpioCode::has_status(NoRangeEvents, raw)
+            );
+            assert_eq!(
+                status.ambient_ready(),
+                !ResultInterruptStatusGpioCode::has_status(NoAmbientEvents, raw)
+            );
+            assert_eq!(
+                status.has_error(),
+                !ResultInterruptStatusGpioCode::has_status(NoError, raw)
+            );
+        }
+    }
+}
+
+#[test]
+fn interrupt_status_unknown_event() {
+    assert_eq!(InterruptStatus::try_from(0b00_000_101), Err(0b00_000_101));
+    assert_eq!(InterruptStatus::try_from(0b00_110_000), Err(0b00_110_000));
+}
@@ -0,0 +1,211 @@
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{MilliLux, Millimeters, RawAmbientCount};
+use crate::{
+    error::Error,
+    mode::{AllowReadMeasurement, ReadyMode},
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod retry_tests;
+
+/// How often and how fast [Retry] retries reads that failed with a
+/// [transient](Error::is_transient) error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RetryPolicy {
+    max_attempts: u8,
+    delay_us: u32,
+}
+
+impl RetryPolicy {
+    /// Create new retry policy with default values.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            delay_us: 1_000,
+        }
+    }
+
+    /// Set the max number of attempts, including the first one.
+    ///
+    /// Min = 1; Default = 3;
+    pub fn set_max_attempts(&mut self, max_attempts: u8) -> Result<(), Error<()>> {
+        if max_attempts < 1 {
+            return Err(Error::InvalidConfigurationValue(max_attempts as u16));
+        }
+        self.max_attempts = max_attempts;
+        Ok(())
+    }
+
+    /// Set the delay in µs between a failed attempt and the next one.
+    ///
+    /// Default = 1000µs;
+    pub fn set_delay_us(&mut self, delay_us: u32) {
+        self.delay_us = delay_us;
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Wrapper around the read methods of a [VL6180X] that retries reads which failed
+/// with a [transient](Error::is_transient) error according to a [RetryPolicy].
+///
+/// Other errors are returned at once, as is the last error once the policy's
+/// attempts are used up. Created with [`VL6180X::with_retry()`].
+#[derive(Debug)]
+pub struct Retry<'a, MODE, I2C: I2c, D> {
+    vl6180x: &'a mut VL6180X<MODE, I2C>,
+    policy: RetryPolicy,
+    delay: &'a mut D,
+}
+
+impl<MODE, I2C, E> VL6180X<MODE, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Wraps the driver to retry reads according to `policy`, using `delay` to wait
+    /// between attempts.
+    pub fn with_retry<'a, D: DelayNs>(
+        &'a mut self,
+        policy: RetryPolicy,
+        delay: &'a mut D,
+    ) -> Retry<'a, MODE, I2C, D> {
+        Retry {
+            vl6180x: self,
+            policy,
+            delay,
+        }
+    }
+}
+
+/// Calls the driver method until it succeeds, fails with an error that is not
+/// transient or the policy's attempts are used up.
+macro_rules! retry {
+    ($self:ident.$method:ident()) => {{
+        let mut attempt = 1;
+        loop {
+            match $self.vl6180x.$method().await {
+                Err(e) if e.is_transient() && attempt < $self.policy.max_attempts => {
+                    attempt += 1;
+                    $self.delay.delay_us($self.policy.delay_us).await;
+                }
+                result => break result,
+            }
+        }
+    }};
+}
+
+impl<'a, MODE, I2C, E, D> Retry<'a, MODE, I2C, D>
+where
+    I2C: I2c<Error = E>,
+    MODE: AllowReadMeasurement,
+    D: DelayNs,
+{
+    /// [`VL6180X::read_range_mm_blocking()`] with retries.
+    pub async fn read_range_mm_blocking(&mut self) -> Result<Millimeters, Error<E>> {
+        retry!(self.read_range_mm_blocking())
+    }
+
+    /// [`VL6180X::read_range_mm()`] with retries.
+    ///
+    /// [Error::ResultNotReady] is transient, so this waits for the result
+    /// for as long as the policy allows.
+    pub async fn read_range_mm(&mut self) -> Result<Millimeters, Error<E>> {
+        retry!(self.read_range_mm())
+    }
+
+    /// [`VL6180X::read_range_blocking()`] with retries.
+    pub async fn read_range_blocking(&mut self) -> Result<Range, Error<E>> {
+        retry!(self.read_range_blocking())
+    }
+
+    /// [`VL6180X::read_range()`] with retries.
+    ///
+    /// [Error::ResultNotReady] is transient, so this waits for the result
+    /// for as long as the policy allows.
+    pub async fn read_range(&mut self) -> Result<Range, Error<E>> {
+        retry!(self.read_range())
+    }
+
+    /// [`VL6180X::read_ambient_lux_blocking()`] with retries.
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux_blocking(&mut self) -> Result<Lux, Error<E>> {
+        retry!(self.read_ambient_lux_blocking())
+    }
+
+    /// [`VL6180X::read_ambient_lux()`] with retries.
+    ///
+    /// [Error::ResultNotReady] is transient, so this waits for the result
+    /// for as long as the policy allows.
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux(&mut self) -> Result<Lux, Error<E>> {
+        retry!(self.read_ambient_lux())
+    }
+
+    /// [`VL6180X::read_ambient_milli_lux_blocking()`] with retries.
+    pub async fn read_ambient_milli_lux_blocking(&mut self) -> Result<MilliLux, Error<E>> {
+        retry!(self.read_ambient_milli_lux_blocking())
+    }
+
+    /// [`VL6180X::read_ambient_milli_lux()`] with retries.
+    ///
+    /// [Error::ResultNotReady] is transient, so this waits for the result
+    /// for as long as the policy allows.
+    pub async fn read_ambient_milli_lux(&mut self) -> Result<MilliLux, Error<E>> {
+        retry!(self.read_ambient_milli_lux())
+    }
+
+    /// [`VL6180X::read_ambient_blocking()`] with retries.
+    pub async fn read_ambient_blocking(&mut self) -> Result<RawAmbientCount, Error<E>> {
+        retry!(self.read_ambient_blocking())
+    }
+
+    /// [`VL6180X::read_ambient()`] with retries.
+    ///
+    /// [Error::ResultNotReady] is transient, so this waits for the result
+    /// for as long as the policy allows.
+    pub async fn read_ambient(&mut self) -> Result<RawAmbientCount, Error<E>> {
+        retry!(self.read_ambient())
+    }
+}
+
+impl<'a, I2C, E, D> Retry<'a, ReadyMode, I2C, D>
+where
+    I2C: I2c<Error = E>,
+    D: DelayNs,
+{
+    /// [`VL6180X::poll_range_mm_single_blocking()`] with retries,
+    /// each attempt starts a new measurement.
+    pub async fn poll_range_mm_single_blocking(&mut self) -> Result<Millimeters, Error<E>> {
+        retry!(self.poll_range_mm_single_blocking())
+    }
+
+    /// [`VL6180X::poll_range_single_blocking()`] with retries,
+    /// each attempt starts a new measurement.
+    pub async fn poll_range_single_blocking(&mut self) -> Result<Range, Error<E>> {
+        retry!(self.poll_range_single_blocking())
+    }
+
+    /// [`VL6180X::poll_ambient_lux_single_blocking()`] with retries,
+    /// each attempt starts a new measurement.
+    #[cfg(feature = "float")]
+    pub async fn poll_ambient_lux_single_blocking(&mut self) -> Result<Lux, Error<E>> {
+        retry!(self.poll_ambient_lux_single_blocking())
+    }
+
+    /// [`VL6180X::poll_ambient_milli_lux_single_blocking()`] with retries,
+    /// each attempt starts a new measurement.
+    pub async fn poll_ambient_milli_lux_single_blocking(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        retry!(self.poll_ambient_milli_lux_single_blocking())
+    }
+}
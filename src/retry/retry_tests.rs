@@ -0,0 +1,113 @@
+use embassy_futures::block_on;
+use embedded_hal::i2c::ErrorKind;
+
+use super::*;
+use crate::{
+    mock::{self, MockDelay},
+    register::RangeStatusErrorCode,
+};
+
+fn policy(max_attempts: u8) -> RetryPolicy {
+    let mut policy = RetryPolicy::new();
+    policy.set_max_attempts(max_attempts).unwrap();
+    policy.set_delay_us(500);
+    policy
+}
+
+#[test]
+fn max_attempts_must_be_at_least_one() {
+    let mut policy = RetryPolicy::new();
+    assert_eq!(
+        policy.set_max_attempts(0),
+        Err(Error::InvalidConfigurationValue(0))
+    );
+    assert_eq!(policy, RetryPolicy::default());
+}
+
+#[test]
+fn retries_bus_errors_until_success() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x062, 50);
+    sensor.com.bus_failures = 2;
+    let mut delay = MockDelay::default();
+    assert_eq!(
+        block_on(sensor.with_retry(policy(3), &mut delay).read_range_mm()),
+        Ok(Millimeters(50))
+    );
+    assert_eq!(delay.total_ns, 2 * 500_000);
+}
+
+#[test]
+fn returns_last_transient_error_when_attempts_are_used_up() {
+    let mut sensor = mock::sensor(ReadyMode);
+    let mut delay = MockDelay::default();
+    assert_eq!(
+        block_on(sensor.with_retry(policy(4), &mut delay).read_ambient()),
+        Err(Error::ResultNotReady)
+    );
+    assert_eq!(delay.total_ns, 3 * 500_000);
+
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.bus_failures = 5;
+    let mut delay = MockDelay::default();
+    assert_eq!(
+        block_on(sensor.with_retry(policy(1), &mut delay).read_range()),
+        Err(Error::BusError(ErrorKind::Bus))
+    );
+    assert_eq!(delay.total_ns, 0);
+}
+
+#[test]
+fn does_not_retry_device_faults() {
+    let mut sensor = mock::sensor(ReadyMode);
+    sensor.com.set(0x04F, 0b00_000_100);
+    sensor.com.set(0x04D, 0b0100_0001);
+    let mut delay = MockDelay::default();
+    assert_eq!(
+        block_on(
+            sensor
+                .with_retry(policy(3), &mut delay)
+                .poll_range_single_blocking()
+        ),
+        Err(Error::RangeStatusError(RangeStatusErrorCode::Pll1Lock))
+    );
+    assert_eq!(delay.total_ns, 0);
+    assert_eq!(
+        sensor
+            .com
+            .writes
+            .iter()
+            .filter(|w| **w == (0x018, 0b01))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn retries_transient_range_status() {
+    let mut sensor = mock::sensor(ReadyMode);
+    // Signal to noise ratio too low, then a valid range
+    sensor
+        .com
+        .range_results
+        .extend([(0b1011_0001, 0), (0x01, 20)]);
+    let mut delay = MockDelay::default();
+    assert_eq!(
+        block_on(
+            sensor
+                .with_retry(policy(2), &mut delay)
+                .poll_range_mm_single_blocking()
+        ),
+        Ok(Millimeters(20))
+    );
+    assert_eq!(
+        sensor
+            .com
+            .writes
+            .iter()
+            .filter(|w| **w == (0x018, 0b01))
+            .count(),
+        2
+    );
+}
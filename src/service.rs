@@ -0,0 +1,258 @@
+//! A ready-made actor owning a [VL6180X](crate::VL6180X), built on `embassy-sync`.
+//!
+//! The [SensorService] reads a sample whenever the interrupt pin fires and publishes
+//! it as an [Event], while taking [Command]s from a channel:
+//!
+//! ```ignore
+//! static COMMANDS: Channel<CriticalSectionRawMutex, Command, 4> = Channel::new();
+//! static EVENTS: Watch<CriticalSectionRawMutex, Event<I2cError, PinError>, 2> = Watch::new();
+//!
+//! let mut service = SensorService::new(vl6180x, EVENTS.sender(), COMMANDS.receiver());
+//! service.run().await
+//! ```
+
+use core::future::{pending, Future};
+
+use embassy_futures::select::{select, Either};
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel, watch};
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{digital::Wait, i2c::I2c};
+
+use crate::{
+    error::{Error, Error2},
+    measurement::Sample,
+    mode::{DynamicMode, OperatingMode},
+    Config, VL6180XwPins,
+};
+
+#[cfg(test)]
+mod service_tests;
+
+/// A request to the [SensorService].
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Command {
+    /// Replaces the config, keeping the I2C address. The sensor is stopped while the
+    /// config is written and then resumes its operating mode. When powered off, the
+    /// config is used once the sensor is powered on again.
+    SetConfig(Config),
+    /// Switches to the operating mode, powering the sensor on or off if needed.
+    SwitchMode(OperatingMode),
+    /// Powers the sensor off, same as switching to [OperatingMode::PoweredOff].
+    PowerOff,
+}
+
+/// What the [SensorService] publishes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Event<E, PE> {
+    /// A new sample.
+    Sample(Sample),
+    /// The operating mode after a [Command], also published if it failed half way.
+    ModeChanged(OperatingMode),
+    /// Reading a sample or carrying out a [Command] failed.
+    Error(Error<E>),
+    /// Setting the `x_shutdown_pin` failed.
+    PinError(PE),
+}
+
+impl<E, PE> Event<E, PE> {
+    fn from_split(error: Result<Error<E>, PE>) -> Self {
+        match error {
+            Ok(e) => Event::Error(e),
+            Err(e) => Event::PinError(e),
+        }
+    }
+}
+
+impl<E, PE> From<Error2<E, PE>> for Event<E, PE> {
+    fn from(error: Error2<E, PE>) -> Self {
+        Self::from_split(error.split_pin_error())
+    }
+}
+
+/// Where the [SensorService] publishes its [Event]s.
+///
+/// Implemented for the senders of an `embassy-sync` [Channel](channel::Channel), which
+/// waits for room so no event is lost, and [Watch](watch::Watch), which only keeps the
+/// latest one.
+pub trait Publish<T> {
+    /// Publishes `value`.
+    fn publish(&mut self, value: T) -> impl Future<Output = ()>;
+}
+
+impl<M: RawMutex, T, const N: usize> Publish<T> for channel::Sender<'_, M, T, N> {
+    async fn publish(&mut self, value: T) {
+        self.send(value).await;
+    }
+}
+
+impl<M: RawMutex, T: Clone, const N: usize> Publish<T> for watch::Sender<'_, M, T, N> {
+    async fn publish(&mut self, value: T) {
+        self.send(value);
+    }
+}
+
+/// Actor owning a [VL6180XwPins] in [DynamicMode].
+///
+/// In the continuous modes it waits for the `interrupt_pin` to go high, reads the
+/// samples that are ready and publishes them. [Command]s are handled as soon as they
+/// arrive, also while waiting for a sample. Errors are published, the service carries
+/// on, device errors are cleared before they are published so the sensor can measure
+/// again while the event waits for room. An `interrupt_pin` error is ignored, the
+/// interrupt status is checked instead.
+///
+/// The config must raise an interrupt for every new sample, which is the default.
+#[derive(Debug)]
+pub struct SensorService<'a, I2C, OP, IP, P, M, const N: usize>
+where
+    I2C: I2c,
+    OP: OutputPin,
+    IP: InputPin,
+    M: RawMutex,
+{
+    sensor: VL6180XwPins<DynamicMode, I2C, OP, IP>,
+    events: P,
+    commands: channel::Receiver<'a, M, Command, N>,
+}
+
+impl<'a, I2C, E, OP, PE, IP, P, M, const N: usize> SensorService<'a, I2C, OP, IP, P, M, N>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin + Wait,
+    P: Publish<Event<E, PE>>,
+    M: RawMutex,
+{
+    /// Create a new service publishing to `events` and taking `commands`.
+    /// It carries on with the current operating mode of the `sensor`.
+    pub fn new(
+        sensor: VL6180XwPins<DynamicMode, I2C, OP, IP>,
+        events: P,
+        commands: channel::Receiver<'a, M, Command, N>,
+    ) -> Self {
+        SensorService {
+            sensor,
+            events,
+            commands,
+        }
+    }
+
+    /// Returns the sensor and the publisher.
+    pub fn into_inner(self) -> (VL6180XwPins<DynamicMode, I2C, OP, IP>, P) {
+        (self.sensor, self.events)
+    }
+
+    /// Runs the service forever.
+    pub async fn run(&mut self) -> ! {
+        loop {
+            self.run_once().await;
+        }
+    }
+
+    /// Handles the next [Command] or interrupt, whichever comes first.
+    ///
+    /// Cancellation safe while waiting, not while the sensor is being read or a
+    /// command is carried out.
+    pub async fn run_once(&mut self) {
+        let continuous = matches!(
+            self.sensor.vl6180x.operating_mode(),
+            OperatingMode::RangeContinuous |
+                OperatingMode::AmbientContinuous |
+                OperatingMode::InterleavedContinuous
+        );
+        let interrupt_pin = &mut self.sensor.interrupt_pin;
+        let interrupt = async {
+            if continuous {
+                let _ = interrupt_pin.wait_for_high().await;
+            } else {
+                pending::<()>().await;
+            }
+        };
+        match select(self.commands.receive(), interrupt).await {
+            Either::First(command) => self.handle_command(command).await,
+            Either::Second(()) => self.read_samples().await,
+        }
+    }
+
+    async fn read_samples(&mut self) {
+        let vl6180x = &mut self.sensor.vl6180x;
+        let status = match vl6180x.read_interrupt_status_checked().await {
+            Ok(status) => status,
+            Err(e) => {
+                // A device error interrupt stays asserted until it is cleared
+                if let Error::LaserSafetyError | Error::PllError = e {
+                    let _ = vl6180x.try_clear_error_interrupt().await;
+                }
+                return self.events.publish(Event::Error(e)).await;
+            }
+        };
+        let mode = vl6180x.operating_mode();
+        if status.ambient_ready() && mode != OperatingMode::RangeContinuous {
+            let event = match vl6180x.try_read_ambient_milli_lux().await {
+                Ok(milli_lux) => Event::Sample(Sample::Ambient(milli_lux)),
+                Err(e) => Event::Error(e),
+            };
+            self.events.publish(event).await;
+        }
+        let vl6180x = &mut self.sensor.vl6180x;
+        if status.range_ready() && mode != OperatingMode::AmbientContinuous {
+            let event = match vl6180x.try_read_range().await {
+                Ok(range) => Event::Sample(Sample::Range(range)),
+                Err(e) => Event::Error(e),
+            };
+            self.events.publish(event).await;
+        }
+    }
+
+    async fn handle_command(&mut self, command: Command) {
+        let result = match command {
+            Command::SetConfig(config) => self.set_config(&config).await,
+            Command::SwitchMode(target) => self.switch_mode(target).await,
+            Command::PowerOff => self.switch_mode(OperatingMode::PoweredOff).await,
+        };
+        if let Err(event) = result {
+            self.events.publish(event).await;
+        }
+        if let Command::SwitchMode(_) | Command::PowerOff = command {
+            let mode = self.sensor.vl6180x.operating_mode();
+            self.events.publish(Event::ModeChanged(mode)).await;
+        }
+    }
+
+    async fn set_config(&mut self, config: &Config) -> Result<(), Event<E, PE>> {
+        let vl6180x = &mut self.sensor.vl6180x;
+        let mode = vl6180x.operating_mode();
+        if mode == OperatingMode::PoweredOff {
+            vl6180x.replace_config(config);
+            return Ok(());
+        }
+        vl6180x
+            .set_operating_mode(OperatingMode::Ready)
+            .await
+            .map_err(Event::Error)?;
+        vl6180x.try_set_config(config).await.map_err(Event::Error)?;
+        vl6180x.set_operating_mode(mode).await.map_err(Event::Error)
+    }
+
+    async fn switch_mode(&mut self, target: OperatingMode) -> Result<(), Event<E, PE>> {
+        let current = self.sensor.vl6180x.operating_mode();
+        if current == target {
+            return Ok(());
+        }
+        if target == OperatingMode::PoweredOff {
+            return self
+                .sensor
+                .try_power_off()
+                .map_err(|e| Event::from_split(e.split_pin_error()));
+        }
+        if current == OperatingMode::PoweredOff {
+            self.sensor.try_power_on_and_init().await?;
+        }
+        self.sensor
+            .vl6180x
+            .set_operating_mode(target)
+            .await
+            .map_err(Event::Error)
+    }
+}
@@ -0,0 +1,184 @@
+use core::convert::Infallible;
+
+use embassy_futures::block_on;
+use embassy_sync::{blocking_mutex::raw::NoopRawMutex, channel::Channel, watch::Watch};
+use embedded_hal::i2c::ErrorKind;
+
+use super::*;
+use crate::{
+    mock::{self, MockI2c, MockPin},
+    units::{Millimeters, Milliseconds},
+    Range,
+};
+
+type TestEvent = Event<ErrorKind, Infallible>;
+
+fn sensor_with_pins(
+    mode: OperatingMode,
+) -> VL6180XwPins<DynamicMode, MockI2c, MockPin, MockPin> {
+    let mut vl6180x = mock::sensor(DynamicMode::new());
+    block_on(vl6180x.set_operating_mode(mode)).unwrap();
+    VL6180XwPins {
+        vl6180x,
+        x_shutdown_pin: MockPin::new(true),
+        interrupt_pin: MockPin::new(true),
+    }
+}
+
+#[test]
+fn publishes_range_samples_on_interrupt() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let mut sensor = sensor_with_pins(OperatingMode::RangeContinuous);
+    sensor.vl6180x.com.set(0x04F, 0b00_000_100);
+    sensor.vl6180x.com.set(0x062, 20);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    block_on(service.run_once());
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::Sample(Sample::Range(Range::Mm(Millimeters(20)))))
+    );
+    let (sensor, _) = service.into_inner();
+    assert_eq!(sensor.interrupt_pin.waits, 1);
+    assert!(sensor.vl6180x.com.was_written(0x015, 0b001));
+}
+
+#[test]
+fn publishes_both_samples_in_interleaved_mode() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let mut sensor = sensor_with_pins(OperatingMode::InterleavedContinuous);
+    sensor.vl6180x.com.set(0x04F, 0b00_100_100);
+    sensor.vl6180x.com.set(0x062, 30);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    block_on(service.run_once());
+    assert!(matches!(
+        events.try_receive(),
+        Ok(Event::Sample(Sample::Ambient(_)))
+    ));
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::Sample(Sample::Range(Range::Mm(Millimeters(30)))))
+    );
+}
+
+#[test]
+fn clears_device_errors_after_publishing_them() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let mut sensor = sensor_with_pins(OperatingMode::RangeContinuous);
+    sensor.vl6180x.com.set(0x04F, 0b01_000_000);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    block_on(service.run_once());
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::Error(Error::LaserSafetyError))
+    );
+    let (sensor, _) = service.into_inner();
+    assert!(sensor.vl6180x.com.was_written(0x015, 0b100));
+}
+
+#[test]
+fn commands_come_before_samples() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Watch::<NoopRawMutex, TestEvent, 1>::new();
+    let mut receiver = events.receiver().unwrap();
+    let mut sensor = sensor_with_pins(OperatingMode::RangeContinuous);
+    sensor.vl6180x.com.set(0x04F, 0b00_000_100);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    commands
+        .try_send(Command::SwitchMode(OperatingMode::AmbientContinuous))
+        .unwrap();
+    block_on(service.run_once());
+    assert_eq!(
+        receiver.try_changed(),
+        Some(Event::ModeChanged(OperatingMode::AmbientContinuous))
+    );
+
+    // A range sample is not read in ambient continuous mode
+    block_on(service.run_once());
+    assert_eq!(receiver.try_changed(), None);
+}
+
+#[test]
+fn set_config_resumes_the_operating_mode() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let sensor = sensor_with_pins(OperatingMode::RangeContinuous);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    let mut config = Config::new();
+    config
+        .set_range_inter_measurement_period(Milliseconds(200))
+        .unwrap();
+    commands.try_send(Command::SetConfig(config)).unwrap();
+    block_on(service.run_once());
+    assert!(events.try_receive().is_err());
+    let (sensor, _) = service.into_inner();
+    assert_eq!(
+        sensor.vl6180x.operating_mode(),
+        OperatingMode::RangeContinuous
+    );
+    assert_eq!(sensor.vl6180x.config.range_inter_measurement_period, 200);
+    assert!(sensor.vl6180x.com.was_written(0x01B, 19));
+}
+
+#[test]
+fn power_off_and_back_on() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let sensor = sensor_with_pins(OperatingMode::RangeContinuous);
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    commands.try_send(Command::PowerOff).unwrap();
+    block_on(service.run_once());
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::ModeChanged(OperatingMode::PoweredOff))
+    );
+
+    let mut config = Config::new();
+    config
+        .set_range_inter_measurement_period(Milliseconds(300))
+        .unwrap();
+    commands.try_send(Command::SetConfig(config)).unwrap();
+    commands
+        .try_send(Command::SwitchMode(OperatingMode::RangeContinuous))
+        .unwrap();
+    block_on(service.run_once());
+    block_on(service.run_once());
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::ModeChanged(OperatingMode::RangeContinuous))
+    );
+    let (sensor, _) = service.into_inner();
+    assert_eq!(sensor.x_shutdown_pin.history, [false, true]);
+    // The config is written when powering on
+    assert!(sensor.vl6180x.com.was_written(0x01B, 29));
+}
+
+#[test]
+fn failed_commands_publish_the_error_and_the_mode() {
+    let commands = Channel::<NoopRawMutex, Command, 2>::new();
+    let events = Channel::<NoopRawMutex, TestEvent, 4>::new();
+    let mut sensor = sensor_with_pins(OperatingMode::Ready);
+    sensor.vl6180x.com.bus_failures = 1;
+    let mut service = SensorService::new(sensor, events.sender(), commands.receiver());
+
+    commands
+        .try_send(Command::SwitchMode(OperatingMode::RangeContinuous))
+        .unwrap();
+    block_on(service.run_once());
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::Error(Error::BusError(ErrorKind::Bus)))
+    );
+    assert_eq!(
+        events.try_receive(),
+        Ok(Event::ModeChanged(OperatingMode::Ready))
+    );
+}
@@ -0,0 +1,347 @@
+//! A [VL6180X] in [DynamicMode] shared between tasks through an `embassy-sync` async mutex.
+
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{Mcps, MilliLux, Millimeters, Milliseconds, RawAmbientCount};
+use crate::{
+    config::{Config, RangeInterruptMode},
+    error::{Error, Error2},
+    mode::{DynamicMode, OperatingMode},
+    register::InterruptErrorCode,
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod shared_tests;
+
+/// Handle to a [VL6180X] in [DynamicMode] whose methods take `&self`, so it can be
+/// shared between tasks, e.g. from a `static`.
+///
+/// Every method locks the sensor for its whole duration, so I2C transactions of
+/// different callers never interleave and a method sees the [OperatingMode] it checked
+/// until it returns. Use [`lock()`](SharedVL6180X::lock) to run several methods
+/// without another task getting in between. `M` is the raw mutex of the lock, e.g.
+/// `CriticalSectionRawMutex` to share it with interrupt executors.
+///
+/// # Cancellation
+///
+/// Dropping a method's future while it waits for the lock is safe, nothing has
+/// happened yet. Once it holds the lock, dropping it stops the sensor access
+/// between two I2C transactions and releases the lock:
+/// - Reads are safe to drop, an unread result stays available to the next read.
+/// - Starting or stopping a mode, [`set_operating_mode()`](SharedVL6180X::set_operating_mode)
+///   and the power methods keep the [OperatingMode] they started from, the next call
+///   repairs the sensor, see [DynamicMode].
+/// - The setters can leave the sensor with part of the new settings.
+pub struct SharedVL6180X<M: RawMutex, I2C: I2c> {
+    vl6180x: Mutex<M, VL6180X<DynamicMode, I2C>>,
+}
+
+impl<M: RawMutex, I2C: I2c> core::fmt::Debug for SharedVL6180X<M, I2C>
+where
+    I2C: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SharedVL6180X")
+            .field("vl6180x", &self.vl6180x)
+            .finish()
+    }
+}
+
+impl<M, I2C, E> SharedVL6180X<M, I2C>
+where
+    M: RawMutex,
+    I2C: I2c<Error = E>,
+{
+    /// Create a new shared handle owning the sensor.
+    pub const fn new(vl6180x: VL6180X<DynamicMode, I2C>) -> Self {
+        SharedVL6180X {
+            vl6180x: Mutex::new(vl6180x),
+        }
+    }
+
+    /// Returns the sensor.
+    pub fn into_inner(self) -> VL6180X<DynamicMode, I2C> {
+        self.vl6180x.into_inner()
+    }
+
+    /// Waits until no one else uses the sensor and locks it until the guard is dropped.
+    pub async fn lock(&self) -> MutexGuard<'_, M, VL6180X<DynamicMode, I2C>> {
+        self.vl6180x.lock().await
+    }
+
+    /// Locks the sensor if no one else uses it, without waiting.
+    ///
+    /// For code that can't await, like an interrupt handler. Returns `None` if a
+    /// task holds the lock, even if that task runs at a lower priority.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, M, VL6180X<DynamicMode, I2C>>> {
+        self.vl6180x.try_lock().ok()
+    }
+
+    /// [`VL6180X::operating_mode()`] of the shared sensor.
+    pub async fn operating_mode(&self) -> OperatingMode {
+        self.lock().await.operating_mode()
+    }
+
+    /// [`VL6180X::try_poll_range_mm_single_blocking()`] on the shared sensor.
+    pub async fn try_poll_range_mm_single_blocking(&self) -> Result<Millimeters, Error<E>> {
+        self.lock().await.try_poll_range_mm_single_blocking().await
+    }
+
+    /// [`VL6180X::try_poll_range_single_blocking()`] on the shared sensor.
+    pub async fn try_poll_range_single_blocking(&self) -> Result<Range, Error<E>> {
+        self.lock().await.try_poll_range_single_blocking().await
+    }
+
+    /// [`VL6180X::try_poll_ambient_lux_single_blocking()`] on the shared sensor.
+    #[cfg(feature = "float")]
+    pub async fn try_poll_ambient_lux_single_blocking(&self) -> Result<Lux, Error<E>> {
+        self.lock()
+            .await
+            .try_poll_ambient_lux_single_blocking()
+            .await
+    }
+
+    /// [`VL6180X::try_poll_ambient_milli_lux_single_blocking()`] on the shared sensor.
+    pub async fn try_poll_ambient_milli_lux_single_blocking(
+        &self,
+    ) -> Result<MilliLux, Error<E>> {
+        self.lock()
+            .await
+            .try_poll_ambient_milli_lux_single_blocking()
+            .await
+    }
+
+    /// [`VL6180X::try_start_range_continuous_mode()`] on the shared sensor.
+    pub async fn try_start_range_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_start_range_continuous_mode().await
+    }
+
+    /// [`VL6180X::try_stop_range_continuous_mode()`] on the shared sensor.
+    pub async fn try_stop_range_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_stop_range_continuous_mode().await
+    }
+
+    /// [`VL6180X::try_start_ambient_continuous_mode()`] on the shared sensor.
+    pub async fn try_start_ambient_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_start_ambient_continuous_mode().await
+    }
+
+    /// [`VL6180X::try_stop_ambient_continuous_mode()`] on the shared sensor.
+    pub async fn try_stop_ambient_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_stop_ambient_continuous_mode().await
+    }
+
+    /// [`VL6180X::try_start_interleaved_continuous_mode()`] on the shared sensor.
+    pub async fn try_start_interleaved_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock()
+            .await
+            .try_start_interleaved_continuous_mode()
+            .await
+    }
+
+    /// [`VL6180X::try_stop_interleaved_continuous_mode()`] on the shared sensor.
+    pub async fn try_stop_interleaved_continuous_mode(&self) -> Result<(), Error<E>> {
+        self.lock()
+            .await
+            .try_stop_interleaved_continuous_mode()
+            .await
+    }
+
+    /// [`VL6180X::set_operating_mode()`] on the shared sensor.
+    pub async fn set_operating_mode(&self, target: OperatingMode) -> Result<(), Error<E>> {
+        self.lock().await.set_operating_mode(target).await
+    }
+
+    /// [`VL6180X::try_start_range_single()`] on the shared sensor.
+    pub async fn try_start_range_single(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_start_range_single().await
+    }
+
+    /// [`VL6180X::try_start_ambient_single()`] on the shared sensor.
+    pub async fn try_start_ambient_single(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_start_ambient_single().await
+    }
+
+    /// [`VL6180X::try_read_range_mm_blocking()`] on the shared sensor.
+    pub async fn try_read_range_mm_blocking(&self) -> Result<Millimeters, Error<E>> {
+        self.lock().await.try_read_range_mm_blocking().await
+    }
+
+    /// [`VL6180X::try_read_range_mm()`] on the shared sensor.
+    pub async fn try_read_range_mm(&self) -> Result<Millimeters, Error<E>> {
+        self.lock().await.try_read_range_mm().await
+    }
+
+    /// [`VL6180X::try_read_range_blocking()`] on the shared sensor.
+    pub async fn try_read_range_blocking(&self) -> Result<Range, Error<E>> {
+        self.lock().await.try_read_range_blocking().await
+    }
+
+    /// [`VL6180X::try_read_range()`] on the shared sensor.
+    pub async fn try_read_range(&self) -> Result<Range, Error<E>> {
+        self.lock().await.try_read_range().await
+    }
+
+    /// [`VL6180X::try_read_ambient_lux_blocking()`] on the shared sensor.
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux_blocking(&self) -> Result<Lux, Error<E>> {
+        self.lock().await.try_read_ambient_lux_blocking().await
+    }
+
+    /// [`VL6180X::try_read_ambient_lux()`] on the shared sensor.
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux(&self) -> Result<Lux, Error<E>> {
+        self.lock().await.try_read_ambient_lux().await
+    }
+
+    /// [`VL6180X::try_read_ambient_milli_lux_blocking()`] on the shared sensor.
+    pub async fn try_read_ambient_milli_lux_blocking(&self) -> Result<MilliLux, Error<E>> {
+        self.lock()
+            .await
+            .try_read_ambient_milli_lux_blocking()
+            .await
+    }
+
+    /// [`VL6180X::try_read_ambient_milli_lux()`] on the shared sensor.
+    pub async fn try_read_ambient_milli_lux(&self) -> Result<MilliLux, Error<E>> {
+        self.lock().await.try_read_ambient_milli_lux().await
+    }
+
+    /// [`VL6180X::try_read_ambient_blocking()`] on the shared sensor.
+    pub async fn try_read_ambient_blocking(&self) -> Result<RawAmbientCount, Error<E>> {
+        self.lock().await.try_read_ambient_blocking().await
+    }
+
+    /// [`VL6180X::try_read_ambient()`] on the shared sensor.
+    pub async fn try_read_ambient(&self) -> Result<RawAmbientCount, Error<E>> {
+        self.lock().await.try_read_ambient().await
+    }
+
+    /// [`VL6180X::try_read_range_return_rate()`] on the shared sensor.
+    pub async fn try_read_range_return_rate(&self) -> Result<Mcps, Error<E>> {
+        self.lock().await.try_read_range_return_rate().await
+    }
+
+    /// [`VL6180X::try_read_device_errors()`] on the shared sensor.
+    pub async fn try_read_device_errors(&self) -> Result<InterruptErrorCode, Error<E>> {
+        self.lock().await.try_read_device_errors().await
+    }
+
+    /// [`VL6180X::try_clear_error_interrupt()`] on the shared sensor.
+    pub async fn try_clear_error_interrupt(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_clear_error_interrupt().await
+    }
+
+    /// [`VL6180X::try_clear_ambient_interrupt()`] on the shared sensor.
+    pub async fn try_clear_ambient_interrupt(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_clear_ambient_interrupt().await
+    }
+
+    /// [`VL6180X::try_clear_range_interrupt()`] on the shared sensor.
+    pub async fn try_clear_range_interrupt(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_clear_range_interrupt().await
+    }
+
+    /// [`VL6180X::try_clear_all_interrupts()`] on the shared sensor.
+    pub async fn try_clear_all_interrupts(&self) -> Result<(), Error<E>> {
+        self.lock().await.try_clear_all_interrupts().await
+    }
+
+    /// [`VL6180X::try_change_i2c_address()`] on the shared sensor.
+    pub async fn try_change_i2c_address(&self, new_address: u8) -> Result<(), Error<E>> {
+        self.lock().await.try_change_i2c_address(new_address).await
+    }
+
+    /// [`VL6180X::try_set_ambient_analogue_gain_level()`] on the shared sensor.
+    pub async fn try_set_ambient_analogue_gain_level(
+        &self,
+        level: u8,
+    ) -> Result<(), Error<E>> {
+        self.lock()
+            .await
+            .try_set_ambient_analogue_gain_level(level)
+            .await
+    }
+
+    /// [`VL6180X::try_set_ambient_integration_period()`] on the shared sensor.
+    pub async fn try_set_ambient_integration_period(
+        &self,
+        time: Milliseconds,
+    ) -> Result<(), Error<E>> {
+        self.lock()
+            .await
+            .try_set_ambient_integration_period(time)
+            .await
+    }
+
+    /// [`VL6180X::try_set_range_result_scaler()`] on the shared sensor.
+    pub async fn try_set_range_result_scaler(&self, scaler: u8) -> Result<(), Error<E>> {
+        self.lock().await.try_set_range_result_scaler(scaler).await
+    }
+
+    /// [`VL6180X::try_set_range_interrupt()`] on the shared sensor.
+    pub async fn try_set_range_interrupt(
+        &self,
+        interrupt_mode: RangeInterruptMode,
+        low_threshold: u8,
+        high_threshold: u8,
+    ) -> Result<(), Error<E>> {
+        self.lock()
+            .await
+            .try_set_range_interrupt(interrupt_mode, low_threshold, high_threshold)
+            .await
+    }
+
+    /// [`VL6180X::try_set_config()`] on the shared sensor.
+    pub async fn try_set_config(&self, config: &Config) -> Result<(), Error<E>> {
+        self.lock().await.try_set_config(config).await
+    }
+
+    /// [`VL6180X::try_power_off()`] on the shared sensor.
+    pub async fn try_power_off<PE, P: OutputPin<Error = PE>>(
+        &self,
+        x_shutdown_pin: &mut P,
+    ) -> Result<(), Error<PE>> {
+        self.lock().await.try_power_off(x_shutdown_pin)
+    }
+
+    /// [`VL6180X::try_power_on_and_init()`] on the shared sensor.
+    pub async fn try_power_on_and_init<PE, P: OutputPin<Error = PE>>(
+        &self,
+        x_shutdown_pin: &mut P,
+    ) -> Result<(), Error2<E, PE>> {
+        self.lock()
+            .await
+            .try_power_on_and_init(x_shutdown_pin)
+            .await
+    }
+
+    /// [`VL6180X::hardware_reset()`] on the shared sensor.
+    pub async fn hardware_reset<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        self.lock()
+            .await
+            .hardware_reset(x_shutdown_pin, delay)
+            .await
+    }
+
+    /// [`VL6180X::recover()`] on the shared sensor.
+    pub async fn recover<PE, P: OutputPin<Error = PE>, D: DelayNs>(
+        &self,
+        x_shutdown_pin: &mut P,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        self.lock().await.recover(x_shutdown_pin, delay).await
+    }
+}
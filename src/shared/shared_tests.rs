@@ -0,0 +1,114 @@
+use core::task::Poll;
+
+use embassy_futures::{block_on, join::join, poll_once};
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+use super::*;
+use crate::mock::{self, poll_then_drop, MockI2c, MockPin};
+
+fn shared(mode: OperatingMode) -> SharedVL6180X<NoopRawMutex, MockI2c> {
+    let mut vl6180x = mock::sensor(DynamicMode::new());
+    block_on(vl6180x.set_operating_mode(mode)).unwrap();
+    SharedVL6180X::new(vl6180x)
+}
+
+#[test]
+fn reads_and_controls_through_a_shared_reference() {
+    let sensor = shared(OperatingMode::Ready);
+    let sensor = &sensor;
+    block_on(sensor.set_operating_mode(OperatingMode::RangeContinuous)).unwrap();
+    assert_eq!(
+        block_on(sensor.operating_mode()),
+        OperatingMode::RangeContinuous
+    );
+
+    {
+        let mut vl6180x = block_on(sensor.lock());
+        vl6180x.com.set(0x04F, 0b00_000_100);
+        vl6180x.com.set(0x062, 20);
+    }
+    assert_eq!(block_on(sensor.try_read_range_mm()), Ok(Millimeters(20)));
+    assert_eq!(
+        block_on(sensor.try_start_range_single()),
+        Err(Error::InvalidMethod(OperatingMode::RangeContinuous))
+    );
+}
+
+#[test]
+fn concurrent_callers_take_turns() {
+    let sensor = shared(OperatingMode::RangeContinuous);
+    {
+        let mut vl6180x = block_on(sensor.lock());
+        vl6180x.com.set(0x04F, 0b00_000_100);
+        vl6180x.com.set(0x062, 20);
+    }
+    let (range, result) = block_on(join(
+        sensor.try_read_range_mm(),
+        sensor.set_operating_mode(OperatingMode::AmbientContinuous),
+    ));
+    // The read completed before the mode changed
+    assert_eq!(range, Ok(Millimeters(20)));
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        block_on(sensor.operating_mode()),
+        OperatingMode::AmbientContinuous
+    );
+}
+
+#[test]
+fn waiting_for_the_lock_is_cancellation_safe() {
+    let sensor = shared(OperatingMode::Ready);
+    let guard = sensor.try_lock().unwrap();
+    assert!(sensor.try_lock().is_none());
+    assert_eq!(
+        poll_once(sensor.set_operating_mode(OperatingMode::RangeContinuous)),
+        Poll::Pending
+    );
+    drop(guard);
+
+    let vl6180x = sensor.into_inner();
+    assert_eq!(vl6180x.operating_mode(), OperatingMode::Ready);
+    assert!(vl6180x.com.writes.is_empty());
+}
+
+#[test]
+fn dropped_reads_leave_the_result_to_the_next_read() {
+    let sensor = shared(OperatingMode::InterleavedContinuous);
+    let mut polls = 1;
+    loop {
+        {
+            let mut vl6180x = block_on(sensor.lock());
+            vl6180x.com.set(0x04F, 0b00_100_100);
+            vl6180x.com.set(0x050, 0x01);
+            vl6180x.com.set(0x051, 0x02);
+            vl6180x.com.set(0x062, 20);
+            vl6180x.com.yield_each_transaction = true;
+        }
+        let range_done = poll_then_drop(sensor.try_read_range_mm(), polls);
+        let ambient_done = poll_then_drop(sensor.try_read_ambient(), polls);
+        if range_done && ambient_done {
+            break;
+        }
+        if !range_done {
+            assert_eq!(block_on(sensor.try_read_range_mm()), Ok(Millimeters(20)));
+        }
+        if !ambient_done {
+            assert_eq!(
+                block_on(sensor.try_read_ambient()),
+                Ok(RawAmbientCount(0x0102))
+            );
+        }
+        polls += 1;
+    }
+}
+
+#[test]
+fn power_methods_take_the_pin() {
+    let sensor = shared(OperatingMode::Ready);
+    let mut pin = MockPin::new(true);
+    block_on(sensor.try_power_off(&mut pin)).unwrap();
+    assert_eq!(block_on(sensor.operating_mode()), OperatingMode::PoweredOff);
+    block_on(sensor.try_power_on_and_init(&mut pin)).unwrap();
+    assert_eq!(block_on(sensor.operating_mode()), OperatingMode::Ready);
+    assert_eq!(pin.history, [false, true]);
+}
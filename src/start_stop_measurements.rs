@@ -1,11 +1,16 @@
 use embedded_hal_async::i2c::I2c;
 
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{MilliLux, Millimeters};
 use crate::{
     error::Error,
+    mode::OperatingMode,
     register::{
         InterleavedModeEnableCode, Register8Bit, SysAmbientStartCode, SysRangeStartCode,
+        RESULT_DEVICE_READY,
     },
-    VL6180X,
+    Range, VL6180X,
 };
 
 impl<MODE, I2C, E> VL6180X<MODE, I2C>
@@ -14,7 +19,7 @@ where
 {
     pub(crate) async fn poll_range_mm_single_blocking_direct(
         &mut self,
-    ) -> Result<u16, Error<E>> {
+    ) -> Result<Millimeters, Error<E>> {
         self.write_named_register(
             Register8Bit::SYSRANGE__START,
             SysRangeStartCode::SingleStart as u8,
@@ -23,9 +28,21 @@ where
         self.read_range_mm_blocking_direct().await
     }
 
+    pub(crate) async fn poll_range_single_blocking_direct(
+        &mut self,
+    ) -> Result<Range, Error<E>> {
+        self.write_named_register(
+            Register8Bit::SYSRANGE__START,
+            SysRangeStartCode::SingleStart as u8,
+        )
+        .await?;
+        self.read_range_blocking_direct().await
+    }
+
+    #[cfg(feature = "float")]
     pub(crate) async fn poll_ambient_lux_single_blocking_direct(
         &mut self,
-    ) -> Result<f32, Error<E>> {
+    ) -> Result<Lux, Error<E>> {
         self.write_named_register(
             Register8Bit::SYSALS__START,
             SysAmbientStartCode::SingleStart as u8,
@@ -34,6 +51,17 @@ where
         self.read_ambient_lux_blocking_direct().await
     }
 
+    pub(crate) async fn poll_ambient_milli_lux_single_blocking_direct(
+        &mut self,
+    ) -> Result<MilliLux, Error<E>> {
+        self.write_named_register(
+            Register8Bit::SYSALS__START,
+            SysAmbientStartCode::SingleStart as u8,
+        )
+        .await?;
+        self.read_ambient_milli_lux_blocking_direct().await
+    }
+
     pub(crate) async fn start_ambient_single_direct(&mut self) -> Result<(), E> {
         self.write_named_register(
             Register8Bit::SYSALS__START,
@@ -71,7 +99,11 @@ where
         &mut self,
     ) -> Result<(), Error<E>> {
         self.check_config_valid()?;
+        self.start_interleaved_continuous_direct().await?;
+        Ok(())
+    }
 
+    async fn start_interleaved_continuous_direct(&mut self) -> Result<(), E> {
         self.write_named_register(
             Register8Bit::INTERLEAVED_MODE__ENABLE,
             InterleavedModeEnableCode::Enable as u8,
@@ -81,8 +113,25 @@ where
             Register8Bit::SYSALS__START,
             SysAmbientStartCode::ContinuousStartOrStop as u8,
         )
-        .await?;
-        Ok(())
+        .await
+    }
+
+    /// Starts the measurements of `operating_mode` on a freshly initialized device,
+    /// which is not taking any measurements.
+    ///
+    /// The config was already checked when the mode was first started, so it isn't again.
+    pub(crate) async fn restart_operating_mode_direct(
+        &mut self,
+        operating_mode: OperatingMode,
+    ) -> Result<(), E> {
+        match operating_mode {
+            OperatingMode::RangeContinuous => self.toggle_range_continuous_direct().await,
+            OperatingMode::AmbientContinuous => self.toggle_ambient_continuous_direct().await,
+            OperatingMode::InterleavedContinuous => {
+                self.start_interleaved_continuous_direct().await
+            }
+            OperatingMode::Ready | OperatingMode::PoweredOff => Ok(()),
+        }
     }
 
     /// For interleaved mode, the following equation must be satisfied:
@@ -92,10 +141,10 @@ where
     /// ≤ `ambient_inter_measurement_period` * 0.9
     ///
     /// The interleaved requirement is only checked when the interleaved mode is started.
-    fn check_config_valid(&self) -> Result<(), Error<E>> {
-        let min_eq_val = (((self.config.range_max_convergence_time + 5) as f32 +
-            self.config.ambient_integration_period as f32 * 1.1) /
-            0.9) as u16;
+    pub(crate) fn check_config_valid(&self) -> Result<(), Error<E>> {
+        let min_eq_val = (((self.config.range_max_convergence_time as u32 + 5) * 10 +
+            self.config.ambient_integration_period as u32 * 11) /
+            9) as u16;
         if self.config.ambient_inter_measurement_period < min_eq_val {
             return Err(Error::InvalidConfigurationValue(
                 self.config.ambient_inter_measurement_period,
@@ -112,4 +161,58 @@ where
         )
         .await
     }
+
+    /// Stops range continuous mode and waits for the measurement in progress to finish,
+    /// so that a new mode can be started straight away.
+    pub(crate) async fn stop_range_continuous_and_wait_direct(
+        &mut self,
+    ) -> Result<(), Error<E>> {
+        self.toggle_range_continuous_direct().await?;
+        self.wait_device_ready(Register8Bit::RESULT__RANGE_STATUS)
+            .await
+    }
+
+    /// Stops ambient continuous mode and waits for the measurement in progress to finish,
+    /// so that a new mode can be started straight away.
+    pub(crate) async fn stop_ambient_continuous_and_wait_direct(
+        &mut self,
+    ) -> Result<(), Error<E>> {
+        self.toggle_ambient_continuous_direct().await?;
+        self.wait_device_ready(Register8Bit::RESULT__ALS_STATUS)
+            .await
+    }
+
+    /// Stops interleaved continuous mode and waits for both the ambient and range
+    /// measurements in progress to finish before disabling the interleaved mode.
+    ///
+    /// Interleaved measurements are driven by the ambient continuous mode, so that is
+    /// what gets stopped.
+    pub(crate) async fn stop_interleaved_continuous_and_wait_direct(
+        &mut self,
+    ) -> Result<(), Error<E>> {
+        self.toggle_ambient_continuous_direct().await?;
+        self.wait_device_ready(Register8Bit::RESULT__ALS_STATUS)
+            .await?;
+        self.wait_device_ready(Register8Bit::RESULT__RANGE_STATUS)
+            .await?;
+        self.stop_interleaved_continuous_direct().await?;
+        Ok(())
+    }
+
+    /// Polls the device ready bit of `status_register` (either RESULT__RANGE_STATUS
+    /// or RESULT__ALS_STATUS) until it is set.
+    pub(crate) async fn wait_device_ready(
+        &mut self,
+        status_register: Register8Bit,
+    ) -> Result<(), Error<E>> {
+        let reg = status_register as u16;
+        let mut c = 0;
+        while self.read_register(reg).await? & RESULT_DEVICE_READY == 0 {
+            c += 1;
+            if c == self.config.poll_max_loop {
+                return Err(Error::Timeout);
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,136 @@
+//! Async streams of the readings of the continuous modes.
+//!
+//! Each `samples()` stream waits on a [Pacer] before every reading, so the bus is only
+//! used once a sample is due. It never ends, errors are yielded and the stream carries on.
+
+use core::future::Future;
+
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+use futures_core::Stream;
+use futures_util::stream::unfold;
+
+use crate::units::{MilliLux, Milliseconds};
+use crate::{
+    error::Error,
+    measurement::Sample,
+    mode::{AmbientContinuousMode, InterleavedContinuousMode, RangeContinuousMode},
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod stream_tests;
+
+/// Waits until the next sample of a `samples()` stream is due.
+pub trait Pacer {
+    /// Returns once the next sample is expected to be ready.
+    fn wait(&mut self) -> impl Future<Output = ()>;
+}
+
+/// Paces a stream by the GPIO1 interrupt pin, which the sensor drives high while a
+/// new sample interrupt is pending.
+///
+/// Works with a borrowed pin too, e.g. `InterruptPacer(&mut interrupt_pin)`.
+/// A pin error is ignored, the reading then polls the interrupt status instead.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct InterruptPacer<P>(pub P);
+
+impl<P: Wait> Pacer for InterruptPacer<P> {
+    async fn wait(&mut self) {
+        let _ = self.0.wait_for_high().await;
+    }
+}
+
+/// Paces a stream by a fixed delay, usually the inter-measurement period.
+///
+/// The reading after the delay polls the interrupt status until the sample is ready,
+/// so a delay a bit shorter than the period keeps up with the device.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct DelayPacer<D> {
+    delay: D,
+    period: Milliseconds,
+}
+
+impl<D: DelayNs> DelayPacer<D> {
+    /// Create a new pacer waiting `period` before every sample.
+    pub fn new(delay: D, period: Milliseconds) -> Self {
+        DelayPacer { delay, period }
+    }
+
+    /// Returns the delay.
+    pub fn into_inner(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayNs> Pacer for DelayPacer<D> {
+    async fn wait(&mut self) {
+        self.delay.delay_ms(self.period.0 as u32).await;
+    }
+}
+
+impl<I2C, E> VL6180X<RangeContinuousMode, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Stream of the range readings, waiting on `pacer` before each one.
+    pub fn samples<'a, P: Pacer + 'a>(
+        &'a mut self,
+        pacer: P,
+    ) -> impl Stream<Item = Result<Range, Error<E>>> + 'a {
+        unfold((self, pacer), |(vl6180x, mut pacer)| async move {
+            pacer.wait().await;
+            let sample = vl6180x.read_range_blocking().await;
+            Some((sample, (vl6180x, pacer)))
+        })
+    }
+}
+
+impl<I2C, E> VL6180X<AmbientContinuousMode, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Stream of the ambient light readings, waiting on `pacer` before each one.
+    pub fn samples<'a, P: Pacer + 'a>(
+        &'a mut self,
+        pacer: P,
+    ) -> impl Stream<Item = Result<MilliLux, Error<E>>> + 'a {
+        unfold((self, pacer), |(vl6180x, mut pacer)| async move {
+            pacer.wait().await;
+            let sample = vl6180x.read_ambient_milli_lux_blocking().await;
+            Some((sample, (vl6180x, pacer)))
+        })
+    }
+}
+
+impl<I2C, E> VL6180X<InterleavedContinuousMode, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Stream of the ambient light and range readings in the order the device measures
+    /// them, waiting on `pacer` before each ambient light reading.
+    ///
+    /// The range reading follows without waiting, the device measures it right after
+    /// the ambient light.
+    pub fn samples<'a, P: Pacer + 'a>(
+        &'a mut self,
+        pacer: P,
+    ) -> impl Stream<Item = Result<Sample, Error<E>>> + 'a {
+        unfold(
+            (self, pacer, false),
+            |(vl6180x, mut pacer, range_next)| async move {
+                let sample = if range_next {
+                    vl6180x.read_range_blocking().await.map(Sample::Range)
+                } else {
+                    pacer.wait().await;
+                    vl6180x
+                        .read_ambient_milli_lux_blocking()
+                        .await
+                        .map(Sample::Ambient)
+                };
+                Some((sample, (vl6180x, pacer, !range_next)))
+            },
+        )
+    }
+}
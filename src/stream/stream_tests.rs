@@ -0,0 +1,89 @@
+use embassy_futures::block_on;
+use futures_util::StreamExt;
+
+use super::*;
+use crate::{
+    mock::{self, MockDelay, MockPin},
+    units::{Millimeters, RawAmbientCount},
+};
+
+fn expected_milli_lux(sensor: &VL6180X<impl Sized, mock::MockI2c>) -> MilliLux {
+    crate::read_measurements::convert_raw_ambient_to_milli_lux(
+        RawAmbientCount(100),
+        sensor.config.ambient_analogue_gain_level,
+        sensor.config.ambient_integration_period,
+    )
+}
+
+#[test]
+fn range_stream_waits_on_the_pin_before_each_reading() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    let mut pin = MockPin::new(true);
+    sensor.com.range_ready(20);
+    {
+        let mut samples = core::pin::pin!(sensor.samples(InterruptPacer(&mut pin)));
+        assert_eq!(
+            block_on(samples.next()),
+            Some(Ok(Range::Mm(Millimeters(20))))
+        );
+    }
+    assert_eq!(pin.waits, 1);
+    assert!(sensor.com.was_written(0x015, 0b001));
+}
+
+#[test]
+fn range_stream_yields_errors_and_carries_on() {
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    let mut delay = MockDelay::default();
+    {
+        let pacer = DelayPacer::new(&mut delay, Milliseconds(100));
+        let mut samples = core::pin::pin!(sensor.samples(pacer));
+        assert_eq!(block_on(samples.next()), Some(Err(Error::Timeout)));
+    }
+    sensor.com.range_ready(30);
+    {
+        let pacer = DelayPacer::new(&mut delay, Milliseconds(100));
+        let mut samples = core::pin::pin!(sensor.samples(pacer));
+        assert_eq!(
+            block_on(samples.next()),
+            Some(Ok(Range::Mm(Millimeters(30))))
+        );
+    }
+    assert_eq!(delay.total_ns, 200_000_000);
+}
+
+#[test]
+fn ambient_stream_yields_milli_lux() {
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    let mut delay = MockDelay::default();
+    sensor.com.ambient_ready();
+    let expected = expected_milli_lux(&sensor);
+    {
+        let pacer = DelayPacer::new(&mut delay, Milliseconds(500));
+        let mut samples = core::pin::pin!(sensor.samples(pacer));
+        assert_eq!(block_on(samples.next()), Some(Ok(expected)));
+    }
+    assert_eq!(delay.total_ns, 500_000_000);
+}
+
+#[test]
+fn interleaved_stream_alternates_ambient_and_range() {
+    let mut sensor = mock::sensor(InterleavedContinuousMode {});
+    let mut pin = MockPin::new(true);
+    sensor.com.ambient_ready();
+    sensor.com.range_ready(40);
+    let expected = expected_milli_lux(&sensor);
+    {
+        let mut samples = core::pin::pin!(sensor.samples(InterruptPacer(&mut pin)));
+        assert_eq!(
+            block_on(samples.next()),
+            Some(Ok(Sample::Ambient(expected)))
+        );
+        assert_eq!(
+            block_on(samples.next()),
+            Some(Ok(Sample::Range(Range::Mm(Millimeters(40)))))
+        );
+    }
+    // Only the ambient light reading waits for the interrupt
+    assert_eq!(pin.waits, 1);
+}
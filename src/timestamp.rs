@@ -0,0 +1,455 @@
+use embedded_hal_async::i2c::I2c;
+
+#[cfg(feature = "float")]
+use crate::units::Lux;
+use crate::units::{MilliLux, Millimeters, RawAmbientCount};
+use crate::{
+    error::Error,
+    mode::{AllowReadMeasurement, CurrentOperatingMode, DynamicMode, OperatingMode},
+    Range, VL6180X,
+};
+
+#[cfg(test)]
+mod timestamp_tests;
+
+/// Monotonic clock the samples are timestamped with.
+///
+/// Implemented for closures returning the time, e.g. `|| timer.now().as_millis() as u32`.
+pub trait Clock {
+    /// Current time in ms. Allowed to wrap around.
+    fn now_ms(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> Clock for F {
+    fn now_ms(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// A reading tagged by a [SampleTagger].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Timestamped<T> {
+    /// The reading.
+    pub value: T,
+    /// Time in ms the reading completed, from the [Clock].
+    pub timestamp_ms: u32,
+    /// Number of the sample among all samples of its kind (range or ambient) the
+    /// device measured since the tagger started, including dropped ones. Wraps around.
+    pub sequence: u32,
+    /// Number of samples measured in continuous mode since the previous reading that
+    /// were overwritten before they were read.
+    pub dropped: u32,
+}
+
+/// Sequence and time of the latest sample of one kind.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+struct Channel {
+    next_sequence: u32,
+    last_ms: Option<u32>,
+}
+
+impl Channel {
+    /// Counts a new sample at `now_ms`, returning its sequence number and the number
+    /// of samples dropped before it, detected from the measurement `period_ms`.
+    fn next(&mut self, now_ms: u32, period_ms: Option<u16>) -> (u32, u32) {
+        let dropped = match (self.last_ms, period_ms) {
+            (Some(last_ms), Some(period_ms)) if period_ms > 0 => {
+                let period_ms = period_ms as u32;
+                // Rounded, so reading up to half a period late is not a drop
+                let periods =
+                    now_ms.wrapping_sub(last_ms).saturating_add(period_ms / 2) / period_ms;
+                periods.saturating_sub(1)
+            }
+            _ => 0,
+        };
+        let sequence = self.next_sequence.wrapping_add(dropped);
+        self.next_sequence = sequence.wrapping_add(1);
+        self.last_ms = Some(now_ms);
+        (sequence, dropped)
+    }
+}
+
+/// Tags the readings of a [VL6180X] with the time from a [Clock] and a sequence number.
+///
+/// In continuous modes the device measures every inter-measurement period and
+/// overwrites a result that was not read in time. Such dropped samples are detected
+/// from the time since the previous reading and the configured
+/// [range_inter_measurement_period](crate::Config::set_range_inter_measurement_period)
+/// or [ambient_inter_measurement_period](crate::Config::set_ambient_inter_measurement_period),
+/// which interleaved mode uses for both kinds. In ready mode every reading is a single
+/// measurement, so none are dropped.
+///
+/// Range and ambient status errors are still a measured sample, so they advance the
+/// sequence although the error is returned. Call [`reset()`](SampleTagger::reset) after
+/// changing mode or period.
+///
+/// The `try_` methods tag the readings of a [DynamicMode] sensor.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct SampleTagger<C> {
+    clock: C,
+    range: Channel,
+    ambient: Channel,
+}
+
+impl<C: Clock> SampleTagger<C> {
+    /// Create a new tagger, the first sample of each kind gets sequence number 0.
+    pub fn new(clock: C) -> Self {
+        SampleTagger {
+            clock,
+            range: Channel::default(),
+            ambient: Channel::default(),
+        }
+    }
+
+    /// Returns the clock.
+    pub fn into_inner(self) -> C {
+        self.clock
+    }
+
+    /// Forgets the previous samples, so the next reading is not compared to them.
+    /// Sequence numbers carry on.
+    pub fn reset(&mut self) {
+        self.range.last_ms = None;
+        self.ambient.last_ms = None;
+    }
+
+    fn tag<T, E>(
+        &mut self,
+        is_range: bool,
+        period_ms: Option<u16>,
+        result: Result<T, Error<E>>,
+    ) -> Result<Timestamped<T>, Error<E>> {
+        match result {
+            Ok(_) | Err(Error::RangeStatusError(_)) | Err(Error::AmbientStatusError(_)) => {}
+            Err(e) => return Err(e),
+        }
+        let timestamp_ms = self.clock.now_ms();
+        let channel = if is_range {
+            &mut self.range
+        } else {
+            &mut self.ambient
+        };
+        let (sequence, dropped) = channel.next(timestamp_ms, period_ms);
+        Ok(Timestamped {
+            value: result?,
+            timestamp_ms,
+            sequence,
+            dropped,
+        })
+    }
+}
+
+/// Inter-measurement period of the range samples in the current operating mode.
+fn range_period<MODE: CurrentOperatingMode, I2C: I2c>(
+    vl6180x: &VL6180X<MODE, I2C>,
+) -> Option<u16> {
+    match vl6180x.mode.current_operating_mode() {
+        OperatingMode::RangeContinuous => Some(vl6180x.config.range_inter_measurement_period),
+        OperatingMode::InterleavedContinuous => {
+            Some(vl6180x.config.ambient_inter_measurement_period)
+        }
+        _ => None,
+    }
+}
+
+/// Inter-measurement period of the ambient samples in the current operating mode.
+fn ambient_period<MODE: CurrentOperatingMode, I2C: I2c>(
+    vl6180x: &VL6180X<MODE, I2C>,
+) -> Option<u16> {
+    match vl6180x.mode.current_operating_mode() {
+        OperatingMode::AmbientContinuous | OperatingMode::InterleavedContinuous => {
+            Some(vl6180x.config.ambient_inter_measurement_period)
+        }
+        _ => None,
+    }
+}
+
+/// Tags the result of a driver read method with the channel of its kind.
+macro_rules! tag {
+    ($self:ident, $vl6180x:ident.$method:ident(), $period:ident, $is_range:expr) => {{
+        let result = $vl6180x.$method().await;
+        let period_ms = $period($vl6180x);
+        $self.tag($is_range, period_ms, result)
+    }};
+}
+
+impl<C: Clock> SampleTagger<C> {
+    /// [`VL6180X::read_range_mm_blocking()`] with a timestamp.
+    pub async fn read_range_mm_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Millimeters>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_range_mm_blocking(), range_period, true)
+    }
+
+    /// [`VL6180X::read_range_mm()`] with a timestamp.
+    pub async fn read_range_mm<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Millimeters>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_range_mm(), range_period, true)
+    }
+
+    /// [`VL6180X::read_range_blocking()`] with a timestamp.
+    pub async fn read_range_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Range>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_range_blocking(), range_period, true)
+    }
+
+    /// [`VL6180X::read_range()`] with a timestamp.
+    pub async fn read_range<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Range>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_range(), range_period, true)
+    }
+
+    /// [`VL6180X::read_ambient_blocking()`] with a timestamp.
+    pub async fn read_ambient_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<RawAmbientCount>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_ambient_blocking(), ambient_period, false)
+    }
+
+    /// [`VL6180X::read_ambient()`] with a timestamp.
+    pub async fn read_ambient<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<RawAmbientCount>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_ambient(), ambient_period, false)
+    }
+
+    /// [`VL6180X::read_ambient_milli_lux_blocking()`] with a timestamp.
+    pub async fn read_ambient_milli_lux_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<MilliLux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(
+            self,
+            vl6180x.read_ambient_milli_lux_blocking(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::read_ambient_milli_lux()`] with a timestamp.
+    pub async fn read_ambient_milli_lux<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<MilliLux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(
+            self,
+            vl6180x.read_ambient_milli_lux(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::read_ambient_lux_blocking()`] with a timestamp.
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux_blocking<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Lux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(
+            self,
+            vl6180x.read_ambient_lux_blocking(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::read_ambient_lux()`] with a timestamp.
+    #[cfg(feature = "float")]
+    pub async fn read_ambient_lux<MODE, I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<MODE, I2C>,
+    ) -> Result<Timestamped<Lux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+        MODE: AllowReadMeasurement + CurrentOperatingMode,
+    {
+        tag!(self, vl6180x.read_ambient_lux(), ambient_period, false)
+    }
+}
+
+impl<C: Clock> SampleTagger<C> {
+    /// [`VL6180X::try_read_range_mm_blocking()`] with a timestamp.
+    pub async fn try_read_range_mm_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Millimeters>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(
+            self,
+            vl6180x.try_read_range_mm_blocking(),
+            range_period,
+            true
+        )
+    }
+
+    /// [`VL6180X::try_read_range_mm()`] with a timestamp.
+    pub async fn try_read_range_mm<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Millimeters>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(self, vl6180x.try_read_range_mm(), range_period, true)
+    }
+
+    /// [`VL6180X::try_read_range_blocking()`] with a timestamp.
+    pub async fn try_read_range_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Range>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(self, vl6180x.try_read_range_blocking(), range_period, true)
+    }
+
+    /// [`VL6180X::try_read_range()`] with a timestamp.
+    pub async fn try_read_range<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Range>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(self, vl6180x.try_read_range(), range_period, true)
+    }
+
+    /// [`VL6180X::try_read_ambient_blocking()`] with a timestamp.
+    pub async fn try_read_ambient_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<RawAmbientCount>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(
+            self,
+            vl6180x.try_read_ambient_blocking(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::try_read_ambient()`] with a timestamp.
+    pub async fn try_read_ambient<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<RawAmbientCount>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(self, vl6180x.try_read_ambient(), ambient_period, false)
+    }
+
+    /// [`VL6180X::try_read_ambient_milli_lux_blocking()`] with a timestamp.
+    pub async fn try_read_ambient_milli_lux_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<MilliLux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(
+            self,
+            vl6180x.try_read_ambient_milli_lux_blocking(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::try_read_ambient_milli_lux()`] with a timestamp.
+    pub async fn try_read_ambient_milli_lux<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<MilliLux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(
+            self,
+            vl6180x.try_read_ambient_milli_lux(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::try_read_ambient_lux_blocking()`] with a timestamp.
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux_blocking<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Lux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(
+            self,
+            vl6180x.try_read_ambient_lux_blocking(),
+            ambient_period,
+            false
+        )
+    }
+
+    /// [`VL6180X::try_read_ambient_lux()`] with a timestamp.
+    #[cfg(feature = "float")]
+    pub async fn try_read_ambient_lux<I2C, E>(
+        &mut self,
+        vl6180x: &mut VL6180X<DynamicMode, I2C>,
+    ) -> Result<Timestamped<Lux>, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        tag!(self, vl6180x.try_read_ambient_lux(), ambient_period, false)
+    }
+}
@@ -0,0 +1,150 @@
+use core::cell::Cell;
+
+use embassy_futures::block_on;
+
+use super::*;
+use crate::{
+    mock,
+    mode::{
+        AmbientContinuousMode, InterleavedContinuousMode, RangeContinuousMode, ReadyMode,
+    },
+    register::RangeStatusErrorCode,
+};
+
+#[test]
+fn channel_counts_dropped_samples() {
+    let mut channel = Channel::default();
+    assert_eq!(channel.next(1_000, Some(100)), (0, 0));
+    assert_eq!(channel.next(1_149, Some(100)), (1, 0));
+    assert_eq!(channel.next(1_300, Some(100)), (3, 1));
+    assert_eq!(channel.next(1_700, None), (4, 0));
+    assert_eq!(channel.next(u32::MAX - 50, None), (5, 0));
+    // The clock wrapped around
+    assert_eq!(channel.next(250, Some(100)), (8, 2));
+    // Nearly a full turn of the clock later
+    assert_eq!(
+        channel.next(249, Some(100)),
+        (8 + u32::MAX / 100, u32::MAX / 100 - 1)
+    );
+}
+
+#[test]
+fn continuous_range_readings_are_tagged() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    assert_eq!(sensor.config.range_inter_measurement_period, 100);
+
+    sensor.com.range_ready(20);
+    now.set(500);
+    assert_eq!(
+        block_on(tagger.read_range_mm(&mut sensor)),
+        Ok(Timestamped {
+            value: Millimeters(20),
+            timestamp_ms: 500,
+            sequence: 0,
+            dropped: 0,
+        })
+    );
+
+    now.set(600);
+    assert_eq!(
+        block_on(tagger.read_range_mm(&mut sensor)),
+        Err(Error::ResultNotReady)
+    );
+
+    sensor.com.range_ready(21);
+    now.set(820);
+    assert_eq!(
+        block_on(tagger.read_range_blocking(&mut sensor)),
+        Ok(Timestamped {
+            value: Range::Mm(Millimeters(21)),
+            timestamp_ms: 820,
+            sequence: 3,
+            dropped: 2,
+        })
+    );
+}
+
+#[test]
+fn status_errors_advance_the_sequence() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(RangeContinuousMode);
+    sensor.com.range_ready(20);
+    sensor.com.set(0x04D, 0b1000_0001);
+    assert_eq!(
+        block_on(tagger.read_range_mm(&mut sensor)),
+        Err(Error::RangeStatusError(RangeStatusErrorCode::RangeIgnore))
+    );
+
+    sensor.com.range_ready(20);
+    now.set(100);
+    let sample = block_on(tagger.read_range_mm(&mut sensor)).unwrap();
+    assert_eq!((sample.sequence, sample.dropped), (1, 0));
+}
+
+#[test]
+fn single_measurements_are_never_dropped() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(ReadyMode);
+    for (i, timestamp_ms) in [0, 10_000, 10_001].iter().enumerate() {
+        sensor.com.range_ready(20);
+        now.set(*timestamp_ms);
+        let sample = block_on(tagger.read_range_mm_blocking(&mut sensor)).unwrap();
+        assert_eq!((sample.sequence, sample.dropped), (i as u32, 0));
+    }
+}
+
+#[test]
+fn ambient_uses_ambient_period() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(AmbientContinuousMode);
+    assert_eq!(sensor.config.ambient_inter_measurement_period, 500);
+    sensor.com.ambient_ready();
+    block_on(tagger.read_ambient(&mut sensor)).unwrap();
+
+    sensor.com.ambient_ready();
+    now.set(1_000);
+    let sample = block_on(tagger.read_ambient_milli_lux(&mut sensor)).unwrap();
+    assert_eq!((sample.sequence, sample.dropped), (2, 1));
+}
+
+#[test]
+fn interleaved_range_uses_ambient_period() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(InterleavedContinuousMode {});
+    sensor.com.range_ready(20);
+    block_on(tagger.read_range_mm(&mut sensor)).unwrap();
+    sensor.com.ambient_ready();
+    block_on(tagger.read_ambient(&mut sensor)).unwrap();
+
+    sensor.com.range_ready(20);
+    now.set(500);
+    let sample = block_on(tagger.read_range_mm(&mut sensor)).unwrap();
+    assert_eq!((sample.sequence, sample.dropped), (1, 0));
+
+    tagger.reset();
+    sensor.com.range_ready(20);
+    now.set(5_000);
+    let sample = block_on(tagger.read_range_mm(&mut sensor)).unwrap();
+    assert_eq!((sample.sequence, sample.dropped), (2, 0));
+}
+
+#[test]
+fn dynamic_mode_uses_current_operating_mode() {
+    let now = Cell::new(0);
+    let mut tagger = SampleTagger::new(|| now.get());
+    let mut sensor = mock::sensor(ReadyMode).into_dynamic_mode();
+    block_on(sensor.try_start_range_continuous_mode()).unwrap();
+    sensor.com.range_ready(20);
+    block_on(tagger.try_read_range_mm(&mut sensor)).unwrap();
+
+    sensor.com.range_ready(20);
+    now.set(300);
+    let sample = block_on(tagger.try_read_range(&mut sensor)).unwrap();
+    assert_eq!((sample.sequence, sample.dropped), (3, 2));
+}
@@ -0,0 +1,280 @@
+use crate::{
+    error::Error,
+    register::RangeStatusErrorCode,
+    units::{Mcps, Millimeters},
+};
+
+#[cfg(test)]
+mod tracking_tests;
+
+/// Range sample with the time it was measured, input to [RangeTracker].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct TrackerSample {
+    /// Time of the measurement in ms, from any monotonic clock. Allowed to wrap around.
+    pub timestamp_ms: u32,
+    /// Range, or the range status error the measurement failed with.
+    pub range: Result<Millimeters, RangeStatusErrorCode>,
+    /// Return signal rate of the measurement, see
+    /// [`read_range_return_rate()`](crate::VL6180X::read_range_return_rate).
+    /// `None` if not read, in which case the nominal measurement noise is used.
+    pub return_rate: Option<Mcps>,
+}
+
+impl TrackerSample {
+    /// Creates a sample from the result of
+    /// [`read_range_mm()`](crate::VL6180X::read_range_mm) or one of its variants.
+    /// Range status errors become part of the sample, other errors are returned.
+    pub fn from_result<E>(
+        timestamp_ms: u32,
+        result: Result<Millimeters, Error<E>>,
+        return_rate: Option<Mcps>,
+    ) -> Result<Self, Error<E>> {
+        let range = match result {
+            Ok(range_mm) => Ok(range_mm),
+            Err(Error::RangeStatusError(code)) => Err(code),
+            Err(e) => return Err(e),
+        };
+        Ok(TrackerSample {
+            timestamp_ms,
+            range,
+            return_rate,
+        })
+    }
+}
+
+/// Tuning of [RangeTracker].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct TrackerConfig {
+    /// Standard deviation of the target's acceleration in mm/s², how quickly the
+    /// tracker follows changes in speed.
+    ///
+    /// Default = 500mm/s²
+    pub acceleration_noise: f32,
+    /// Standard deviation in mm of a measurement with the
+    /// [reference_return_rate](TrackerConfig::reference_return_rate).
+    ///
+    /// Default = 3mm
+    pub measurement_noise_mm: f32,
+    /// Return signal rate at which the measurement noise is
+    /// [measurement_noise_mm](TrackerConfig::measurement_noise_mm). The measurement
+    /// variance scales with `reference_return_rate / return_rate`.
+    ///
+    /// Default = 5 MCPS
+    pub reference_return_rate: Mcps,
+    /// Samples further from the prediction than this many standard deviations are
+    /// rejected as outliers.
+    ///
+    /// Default = 4
+    pub gate_sigmas: f32,
+    /// Number of consecutive outliers after which the tracker assumes the target really
+    /// moved and restarts at the new distance.
+    ///
+    /// Default = 3
+    pub max_outliers: u8,
+    /// Time in ms without an accepted measurement after which the track is dropped.
+    ///
+    /// Default = 1000ms
+    pub max_prediction_ms: u32,
+}
+
+impl TrackerConfig {
+    /// Create new tracker config with default values.
+    pub fn new() -> Self {
+        TrackerConfig {
+            acceleration_noise: 500.0,
+            measurement_noise_mm: 3.0,
+            reference_return_rate: Mcps(5 << 7),
+            gate_sigmas: 4.0,
+            max_outliers: 3,
+            max_prediction_ms: 1_000,
+        }
+    }
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What [RangeTracker] did with a sample.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum TrackerUpdate {
+    /// A new track was started at the measured distance.
+    Started,
+    /// The measurement was used to update the track.
+    Accepted,
+    /// The measurement was too far from the prediction and ignored.
+    Outlier,
+    /// The measurement failed with a range status error, the track was only predicted.
+    Skipped(RangeStatusErrorCode),
+}
+
+/// Estimate of the target returned by [RangeTracker].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct TrackerEstimate {
+    /// Filtered distance to the target in mm.
+    pub distance_mm: f32,
+    /// Speed of the target in mm/s, negative when approaching the sensor.
+    pub velocity_mm_s: f32,
+    /// Confidence in the distance between 0 and 1. Is 0.5 when the distance is as
+    /// uncertain as a single nominal measurement, and drops while samples are
+    /// skipped or rejected.
+    pub confidence: f32,
+    /// What the tracker did with the latest sample.
+    pub update: TrackerUpdate,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+struct Track {
+    distance_mm: f32,
+    velocity_mm_s: f32,
+    // Covariance of distance and velocity
+    p00: f32,
+    p01: f32,
+    p11: f32,
+    timestamp_ms: u32,
+    last_accepted_ms: u32,
+    outliers: u8,
+}
+
+/// Tracks the distance and speed of a single target with a constant velocity
+/// Kalman filter, rejecting outliers from crosstalk and ambient interference.
+///
+/// Measurements with a low return signal rate are trusted less, and measurements that
+/// failed with a [RangeStatusErrorCode] only advance the prediction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RangeTracker {
+    config: TrackerConfig,
+    track: Option<Track>,
+}
+
+impl RangeTracker {
+    /// Create a new tracker without a track.
+    pub fn new(config: TrackerConfig) -> Self {
+        RangeTracker {
+            config,
+            track: None,
+        }
+    }
+
+    /// Drops the current track.
+    pub fn reset(&mut self) {
+        self.track = None;
+    }
+
+    /// Adds a sample, samples must be added in the order they were measured.
+    /// Returns the new estimate, or `None` if there is no track because no valid
+    /// measurement was accepted within
+    /// [max_prediction_ms](TrackerConfig::max_prediction_ms).
+    pub fn update(&mut self, sample: TrackerSample) -> Option<TrackerEstimate> {
+        let measurement_variance = self.measurement_variance(sample.return_rate);
+        let config = self.config;
+        let track = match (&mut self.track, sample.range) {
+            (None, Ok(Millimeters(range_mm))) => {
+                self.track = Some(Track {
+                    distance_mm: range_mm as f32,
+                    velocity_mm_s: 0.0,
+                    p00: measurement_variance,
+                    p01: 0.0,
+                    p11: config.acceleration_noise * config.acceleration_noise,
+                    timestamp_ms: sample.timestamp_ms,
+                    last_accepted_ms: sample.timestamp_ms,
+                    outliers: 0,
+                });
+                return self.estimate(TrackerUpdate::Started);
+            }
+            (None, Err(_)) => return None,
+            (Some(track), _) => track,
+        };
+
+        if sample.timestamp_ms.wrapping_sub(track.last_accepted_ms) > config.max_prediction_ms
+        {
+            self.track = None;
+            return self.update(sample);
+        }
+        track.predict(sample.timestamp_ms, config.acceleration_noise);
+
+        let range_mm = match sample.range {
+            Ok(Millimeters(range_mm)) => range_mm as f32,
+            Err(code) => return self.estimate(TrackerUpdate::Skipped(code)),
+        };
+        let innovation = range_mm - track.distance_mm;
+        let innovation_variance = track.p00 + measurement_variance;
+        let gate = config.gate_sigmas * config.gate_sigmas * innovation_variance;
+        if innovation * innovation > gate {
+            track.outliers += 1;
+            if track.outliers >= config.max_outliers {
+                self.track = None;
+                return self.update(sample);
+            }
+            return self.estimate(TrackerUpdate::Outlier);
+        }
+
+        let k0 = track.p00 / innovation_variance;
+        let k1 = track.p01 / innovation_variance;
+        track.distance_mm += k0 * innovation;
+        track.velocity_mm_s += k1 * innovation;
+        track.p11 -= k1 * track.p01;
+        track.p01 *= 1.0 - k0;
+        track.p00 *= 1.0 - k0;
+        track.last_accepted_ms = sample.timestamp_ms;
+        track.outliers = 0;
+        self.estimate(TrackerUpdate::Accepted)
+    }
+
+    /// Current estimate without adding a sample, `None` if there is no track.
+    pub fn current(&self) -> Option<TrackerEstimate> {
+        self.track.map(|track| TrackerEstimate {
+            distance_mm: track.distance_mm,
+            velocity_mm_s: track.velocity_mm_s,
+            confidence: self.confidence(&track),
+            update: TrackerUpdate::Accepted,
+        })
+    }
+
+    fn estimate(&self, update: TrackerUpdate) -> Option<TrackerEstimate> {
+        self.current()
+            .map(|estimate| TrackerEstimate { update, ..estimate })
+    }
+
+    fn confidence(&self, track: &Track) -> f32 {
+        let nominal_variance =
+            self.config.measurement_noise_mm * self.config.measurement_noise_mm;
+        nominal_variance / (nominal_variance + track.p00)
+    }
+
+    /// Variance of a measurement with the given return rate, which is inversely
+    /// proportional to the return rate, within 1/16 and 256 times the nominal variance.
+    fn measurement_variance(&self, return_rate: Option<Mcps>) -> f32 {
+        let nominal_variance =
+            self.config.measurement_noise_mm * self.config.measurement_noise_mm;
+        match return_rate {
+            None => nominal_variance,
+            Some(return_rate) => {
+                let factor = self.config.reference_return_rate.to_f32() /
+                    return_rate.to_f32().max(1.0 / 128.0);
+                nominal_variance * factor.clamp(1.0 / 16.0, 256.0)
+            }
+        }
+    }
+}
+
+impl Track {
+    fn predict(&mut self, timestamp_ms: u32, acceleration_noise: f32) {
+        let dt = timestamp_ms.wrapping_sub(self.timestamp_ms) as f32 / 1000.0;
+        self.timestamp_ms = timestamp_ms;
+        let q = acceleration_noise * acceleration_noise;
+        let dt2 = dt * dt;
+        self.distance_mm += self.velocity_mm_s * dt;
+        self.p00 += 2.0 * dt * self.p01 + dt2 * self.p11 + q * dt2 * dt2 / 4.0;
+        self.p01 += dt * self.p11 + q * dt2 * dt / 2.0;
+        self.p11 += q * dt2;
+    }
+}
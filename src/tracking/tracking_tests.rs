@@ -0,0 +1,158 @@
+use super::*;
+
+fn sample(timestamp_ms: u32, range_mm: u16) -> TrackerSample {
+    TrackerSample {
+        timestamp_ms,
+        range: Ok(Millimeters(range_mm)),
+        return_rate: None,
+    }
+}
+
+fn steady_tracker(range_mm: u16) -> RangeTracker {
+    let mut tracker = RangeTracker::new(TrackerConfig::new());
+    for i in 0..20 {
+        tracker.update(sample(i * 10, range_mm));
+    }
+    tracker
+}
+
+#[test]
+fn first_sample_starts_track() {
+    let mut tracker = RangeTracker::new(TrackerConfig::new());
+    assert_eq!(tracker.current(), None);
+    let no_target = TrackerSample {
+        timestamp_ms: 0,
+        range: Err(RangeStatusErrorCode::MaxConvergence),
+        return_rate: None,
+    };
+    assert_eq!(tracker.update(no_target), None);
+    assert_eq!(
+        tracker.update(sample(10, 120)),
+        Some(TrackerEstimate {
+            distance_mm: 120.0,
+            velocity_mm_s: 0.0,
+            confidence: 0.5,
+            update: TrackerUpdate::Started,
+        })
+    );
+}
+
+#[test]
+fn estimates_approach_speed() {
+    let mut tracker = RangeTracker::new(TrackerConfig::new());
+    let mut estimate = None;
+    // Approaching at 100mm/s, sampled every 10ms
+    for i in 0..100 {
+        estimate = tracker.update(sample(i * 10, 200 - i as u16));
+    }
+    let estimate = estimate.unwrap();
+    assert_eq!(estimate.update, TrackerUpdate::Accepted);
+    assert!((estimate.distance_mm - 101.0) * (estimate.distance_mm - 101.0) < 1.0);
+    assert!((estimate.velocity_mm_s + 100.0) * (estimate.velocity_mm_s + 100.0) < 25.0);
+    assert!(estimate.confidence > 0.5);
+}
+
+#[test]
+fn rejects_spikes() {
+    let mut tracker = steady_tracker(100);
+    let estimate = tracker.update(sample(200, 180)).unwrap();
+    assert_eq!(estimate.update, TrackerUpdate::Outlier);
+    assert!((estimate.distance_mm - 100.0) * (estimate.distance_mm - 100.0) < 0.01);
+    let estimate = tracker.update(sample(210, 100)).unwrap();
+    assert_eq!(estimate.update, TrackerUpdate::Accepted);
+}
+
+#[test]
+fn restarts_after_consecutive_outliers() {
+    let mut tracker = steady_tracker(100);
+    assert_eq!(
+        tracker.update(sample(200, 30)).unwrap().update,
+        TrackerUpdate::Outlier
+    );
+    assert_eq!(
+        tracker.update(sample(210, 30)).unwrap().update,
+        TrackerUpdate::Outlier
+    );
+    let estimate = tracker.update(sample(220, 30)).unwrap();
+    assert_eq!(estimate.update, TrackerUpdate::Started);
+    assert_eq!(estimate.distance_mm, 30.0);
+}
+
+#[test]
+fn skipped_samples_lower_confidence() {
+    let mut tracker = steady_tracker(100);
+    let before = tracker.current().unwrap().confidence;
+    let estimate = tracker
+        .update(TrackerSample {
+            timestamp_ms: 200,
+            range: Err(RangeStatusErrorCode::MaxSignalToNoiseRatio),
+            return_rate: None,
+        })
+        .unwrap();
+    assert_eq!(
+        estimate.update,
+        TrackerUpdate::Skipped(RangeStatusErrorCode::MaxSignalToNoiseRatio)
+    );
+    assert!(estimate.confidence < before);
+}
+
+#[test]
+fn low_return_rate_is_trusted_less() {
+    let mut strong = steady_tracker(100);
+    let mut weak = steady_tracker(100);
+    let strong = strong
+        .update(TrackerSample {
+            return_rate: Some(Mcps(20 << 7)),
+            ..sample(200, 105)
+        })
+        .unwrap();
+    let weak = weak
+        .update(TrackerSample {
+            return_rate: Some(Mcps(1 << 6)),
+            ..sample(200, 105)
+        })
+        .unwrap();
+    assert_eq!(strong.update, TrackerUpdate::Accepted);
+    assert_eq!(weak.update, TrackerUpdate::Accepted);
+    assert!(weak.distance_mm < strong.distance_mm);
+}
+
+#[test]
+fn drops_track_without_measurements() {
+    let mut tracker = steady_tracker(100);
+    let no_target = TrackerSample {
+        timestamp_ms: 1_500,
+        range: Err(RangeStatusErrorCode::MaxConvergence),
+        return_rate: None,
+    };
+    assert_eq!(tracker.update(no_target), None);
+    assert_eq!(tracker.current(), None);
+}
+
+#[test]
+fn handles_timestamp_wrap_around() {
+    let mut tracker = RangeTracker::new(TrackerConfig::new());
+    tracker.update(sample(u32::MAX - 5, 100));
+    let estimate = tracker.update(sample(4, 100)).unwrap();
+    assert_eq!(estimate.update, TrackerUpdate::Accepted);
+}
+
+#[test]
+fn sample_from_result() {
+    assert_eq!(
+        TrackerSample::from_result::<()>(
+            5,
+            Err(Error::RangeStatusError(RangeStatusErrorCode::RangeIgnore)),
+            Some(Mcps(3))
+        ),
+        Ok(TrackerSample {
+            timestamp_ms: 5,
+            range: Err(RangeStatusErrorCode::RangeIgnore),
+            return_rate: Some(Mcps(3)),
+        })
+    );
+    assert_eq!(
+        TrackerSample::from_result(5, Err(Error::BusError(())), None),
+        Err(Error::BusError(()))
+    );
+}
@@ -0,0 +1,94 @@
+//! Units of the measurement and [Config](crate::Config) values.
+//!
+//! Each is a newtype over the integer the device works with, so a raw ambient count
+//! can't be passed where lux or a distance is expected. The value is the public
+//! field, `Millimeters(100).0 == 100`.
+//!
+//! With the `uom` feature the units convert into the quantities of the
+//! [uom](https://docs.rs/uom) crate. uom has no illuminance quantity, so lux stay as they are.
+
+#[cfg(test)]
+mod units_tests;
+
+/// Distance in mm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Millimeters(pub u16);
+
+/// Duration in ms.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Milliseconds(pub u16);
+
+/// Raw ambient light count, as measured with the analogue gain and integration period
+/// the measurement ran with. Convert it to [MilliLux] or [Lux] with those settings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RawAmbientCount(pub u16);
+
+/// Ambient light in milli-lux.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct MilliLux(pub u32);
+
+/// Ambient light in lux.
+#[cfg(feature = "float")]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Lux(pub f32);
+
+#[cfg(feature = "float")]
+impl From<MilliLux> for Lux {
+    fn from(milli_lux: MilliLux) -> Self {
+        Lux(milli_lux.0 as f32 / 1000.0)
+    }
+}
+
+/// Return signal rate in MCPS (mega counts per second), as the 9.7 fixed point
+/// value the device reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Mcps(pub u16);
+
+impl Mcps {
+    /// The rate in kilo counts per second, rounded down.
+    pub fn kcps(&self) -> u32 {
+        self.0 as u32 * 1000 / 128
+    }
+
+    /// The rate in MCPS.
+    #[cfg(feature = "float")]
+    pub fn to_f32(&self) -> f32 {
+        self.0 as f32 / 128.0
+    }
+}
+
+#[cfg(feature = "uom")]
+mod uom_conversions {
+    use uom::si::{
+        f32::{Frequency, Length, Time},
+        frequency::megahertz,
+        length::millimeter,
+        time::millisecond,
+    };
+
+    use super::{Mcps, Millimeters, Milliseconds};
+
+    impl From<Millimeters> for Length {
+        fn from(distance: Millimeters) -> Self {
+            Length::new::<millimeter>(distance.0 as f32)
+        }
+    }
+
+    impl From<Milliseconds> for Time {
+        fn from(duration: Milliseconds) -> Self {
+            Time::new::<millisecond>(duration.0 as f32)
+        }
+    }
+
+    impl From<Mcps> for Frequency {
+        fn from(rate: Mcps) -> Self {
+            Frequency::new::<megahertz>(rate.to_f32())
+        }
+    }
+}
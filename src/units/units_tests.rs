@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn units_compare_by_value() {
+    assert!(Millimeters(50) < Millimeters(80));
+    assert_eq!(Milliseconds::default(), Milliseconds(0));
+    assert!(RawAmbientCount(u16::MAX) > RawAmbientCount(1));
+}
+
+#[test]
+fn mcps_conversions() {
+    assert_eq!(Mcps(5 << 7).kcps(), 5_000);
+    assert_eq!(Mcps(1).kcps(), 7);
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn float_conversions() {
+    assert_eq!(Lux::from(MilliLux(1_500)), Lux(1.5));
+    assert_eq!(Mcps(3 << 6).to_f32(), 1.5);
+}
+
+#[cfg(feature = "uom")]
+#[test]
+fn uom_conversions() {
+    use uom::si::{
+        f32::{Frequency, Length, Time},
+        frequency::hertz,
+        length::meter,
+        time::second,
+    };
+
+    assert!((Length::from(Millimeters(250)).get::<meter>() - 0.25).abs() < 1e-6);
+    assert!((Time::from(Milliseconds(1_500)).get::<second>() - 1.5).abs() < 1e-6);
+    assert!((Frequency::from(Mcps(1 << 6)).get::<hertz>() - 500_000.0).abs() < 1.0);
+}
@@ -0,0 +1,180 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    error::{Error, Error2},
+    mode::{AllowCommunication, AllowRecovery, DynamicMode, PoweredOffMode, ReadyMode},
+    Config, VL6180XwPins, VL6180X,
+};
+
+#[cfg(test)]
+mod with_pins_tests;
+
+impl<MODE, I2C, E, OP, PE, IP> VL6180XwPins<MODE, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+{
+    fn into_mode<MODE2>(self, mode: MODE2) -> VL6180XwPins<MODE2, I2C, OP, IP> {
+        VL6180XwPins {
+            vl6180x: self.vl6180x.into_mode(mode),
+            x_shutdown_pin: self.x_shutdown_pin,
+            interrupt_pin: self.interrupt_pin,
+        }
+    }
+
+    /// Returns whether the sensor is asserting an interrupt on the `interrupt_pin`.
+    ///
+    /// GPIO1 is configured as active high, see
+    /// [`read_interrupt_status()`](VL6180X::read_interrupt_status) for which interrupt it is.
+    pub fn is_interrupt_asserted(&mut self) -> Result<bool, Error<IP::Error>> {
+        self.interrupt_pin.is_high().map_err(Error::GpioPinError)
+    }
+}
+
+impl<I2C, E, OP, PE, IP> VL6180XwPins<ReadyMode, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+{
+    /// Create a new VL6180X driver that manages its pins.
+    ///
+    /// Powers on the sensor by setting the `x_shutdown_pin` high.
+    /// It then busy waits for the device to be booted and initializes the device.
+    pub async fn new(
+        i2c: I2C,
+        x_shutdown_pin: OP,
+        interrupt_pin: IP,
+    ) -> Result<Self, Error2<E, PE>> {
+        let default_config = &Config::new();
+        Self::with_config(i2c, default_config, x_shutdown_pin, interrupt_pin).await
+    }
+
+    /// Create a new VL6180X driver that manages its pins, cloning provided config values.
+    ///
+    /// Powers on the sensor by setting the `x_shutdown_pin` high.
+    /// It then busy waits for the device to be booted, initializes the device and
+    /// switches it to the configured I2C address.
+    pub async fn with_config(
+        i2c: I2C,
+        config: &Config,
+        mut x_shutdown_pin: OP,
+        interrupt_pin: IP,
+    ) -> Result<Self, Error2<E, PE>> {
+        let mut vl6180x = VL6180X {
+            mode: ReadyMode,
+            com: i2c,
+            config: *config,
+        };
+        vl6180x
+            .power_on_and_init_direct(&mut x_shutdown_pin)
+            .await?;
+        Ok(Self {
+            vl6180x,
+            x_shutdown_pin,
+            interrupt_pin,
+        })
+    }
+
+    /// Make VL6180X dynamic, see [`VL6180X::into_dynamic_mode()`].
+    pub fn into_dynamic_mode(self) -> VL6180XwPins<DynamicMode, I2C, OP, IP> {
+        self.into_mode(DynamicMode::new())
+    }
+}
+
+impl<MODE, I2C, E, OP, PE, IP> VL6180XwPins<MODE, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+    MODE: AllowCommunication,
+{
+    /// Powers off the sensor by setting the `x_shutdown_pin` low.
+    pub fn power_off(
+        mut self,
+    ) -> Result<VL6180XwPins<PoweredOffMode, I2C, OP, IP>, Error<PE>> {
+        self.vl6180x.power_off_direct(&mut self.x_shutdown_pin)?;
+        Ok(self.into_mode(PoweredOffMode {}))
+    }
+
+    /// Resets the sensor by pulsing the `x_shutdown_pin` low.
+    /// It then busy waits for the device to be booted and initializes the device
+    /// with the stored config.
+    pub async fn hardware_reset<D: DelayNs>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<VL6180XwPins<ReadyMode, I2C, OP, IP>, Error2<E, PE>> {
+        self.vl6180x
+            .hardware_reset_direct(&mut self.x_shutdown_pin, delay)
+            .await?;
+        Ok(self.into_mode(ReadyMode))
+    }
+}
+
+impl<MODE, I2C, E, OP, PE, IP> VL6180XwPins<MODE, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+    MODE: AllowRecovery,
+{
+    /// Recovers the sensor after a device or bus fault using the `x_shutdown_pin`,
+    /// see [`VL6180X::recover()`].
+    pub async fn recover<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error2<E, PE>> {
+        self.vl6180x.recover(&mut self.x_shutdown_pin, delay).await
+    }
+}
+
+impl<I2C, E, OP, PE, IP> VL6180XwPins<PoweredOffMode, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+{
+    /// Powers on the sensor by setting the `x_shutdown_pin` high.
+    /// It then busy waits for the device to be booted and initializes the device.
+    pub async fn power_on_and_init(
+        mut self,
+    ) -> Result<VL6180XwPins<ReadyMode, I2C, OP, IP>, Error2<E, PE>> {
+        self.vl6180x
+            .power_on_and_init_direct(&mut self.x_shutdown_pin)
+            .await?;
+        Ok(self.into_mode(ReadyMode))
+    }
+}
+
+impl<I2C, E, OP, PE, IP> VL6180XwPins<DynamicMode, I2C, OP, IP>
+where
+    I2C: I2c<Error = E>,
+    OP: OutputPin<Error = PE>,
+    IP: InputPin,
+{
+    /// Same functionality as [`VL6180X::try_power_off()`] using the `x_shutdown_pin`.
+    pub fn try_power_off(&mut self) -> Result<(), Error<PE>> {
+        self.vl6180x.try_power_off(&mut self.x_shutdown_pin)
+    }
+
+    /// Same functionality as [`VL6180X::try_power_on_and_init()`] using the `x_shutdown_pin`.
+    pub async fn try_power_on_and_init(&mut self) -> Result<(), Error2<E, PE>> {
+        self.vl6180x
+            .try_power_on_and_init(&mut self.x_shutdown_pin)
+            .await
+    }
+
+    /// Same functionality as [`VL6180X::hardware_reset()`] using the `x_shutdown_pin`.
+    pub async fn hardware_reset<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error2<E, PE>> {
+        self.vl6180x
+            .hardware_reset(&mut self.x_shutdown_pin, delay)
+            .await
+    }
+
+    /// Same functionality as [`VL6180X::recover()`] using the `x_shutdown_pin`.
+    pub async fn recover<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error2<E, PE>> {
+        self.vl6180x.recover(&mut self.x_shutdown_pin, delay).await
+    }
+}
@@ -0,0 +1,95 @@
+use embassy_futures::block_on;
+
+use super::*;
+use crate::{
+    mock::{self, MockDelay, MockI2c, MockPin},
+    mode::{OperatingMode, RangeContinuousMode},
+};
+
+fn sensor_with_pins<MODE>(mode: MODE) -> VL6180XwPins<MODE, MockI2c, MockPin, MockPin> {
+    VL6180XwPins {
+        vl6180x: mock::sensor(mode),
+        x_shutdown_pin: MockPin::new(true),
+        interrupt_pin: MockPin::new(false),
+    }
+}
+
+#[test]
+fn new_powers_on_and_initializes() {
+    let sensor = block_on(VL6180XwPins::new(
+        MockI2c::new(),
+        MockPin::new(false),
+        MockPin::new(false),
+    ))
+    .unwrap();
+    assert_eq!(sensor.x_shutdown_pin.history, [true]);
+    // init_hardware clears SYSTEM__FRESH_OUT_OF_RESET
+    assert!(sensor.vl6180x.com.was_written(0x016, 0));
+}
+
+#[test]
+fn new_rejects_unknown_device() {
+    let mut i2c = MockI2c::new();
+    i2c.set(0x000, 0xAA);
+    let result = block_on(VL6180XwPins::new(
+        i2c,
+        MockPin::new(false),
+        MockPin::new(false),
+    ));
+    assert_eq!(result.err(), Some(Error2::InvalidDevice(0xAA)));
+}
+
+#[test]
+fn with_config_switches_to_the_configured_address() {
+    let mut config = Config::new();
+    config.set_i2c_address(0x30);
+    let mut sensor = block_on(VL6180XwPins::with_config(
+        MockI2c::new(),
+        &config,
+        MockPin::new(false),
+        MockPin::new(false),
+    ))
+    .unwrap();
+    assert!(sensor.vl6180x.com.was_written(0x212, 0x30));
+    assert_eq!(sensor.vl6180x.com.device_address, 0x30);
+    assert_eq!(sensor.vl6180x.config.address, 0x30);
+    assert_eq!(block_on(sensor.vl6180x.read_model_id()), Ok(0xB4));
+}
+
+#[test]
+fn power_off_and_on_drive_the_pin() {
+    let sensor = sensor_with_pins(RangeContinuousMode).power_off().unwrap();
+    assert!(!sensor.x_shutdown_pin.is_high);
+    let sensor = block_on(sensor.power_on_and_init()).unwrap();
+    assert_eq!(sensor.x_shutdown_pin.history, [false, true]);
+}
+
+#[test]
+fn hardware_reset_pulses_the_pin() {
+    let mut delay = MockDelay::default();
+    let sensor = block_on(sensor_with_pins(ReadyMode).hardware_reset(&mut delay)).unwrap();
+    assert_eq!(sensor.x_shutdown_pin.history, [false, true]);
+    assert!(delay.total_ns > 0);
+}
+
+#[test]
+fn dynamic_mode_keeps_pin_and_mode_in_sync() {
+    let mut sensor = sensor_with_pins(ReadyMode).into_dynamic_mode();
+    sensor.try_power_off().unwrap();
+    assert!(!sensor.x_shutdown_pin.is_high);
+    assert_eq!(
+        sensor.try_power_off(),
+        Err(Error::InvalidMethod(OperatingMode::PoweredOff))
+    );
+    block_on(sensor.try_power_on_and_init()).unwrap();
+    assert!(sensor.x_shutdown_pin.is_high);
+    assert_eq!(sensor.vl6180x.operating_mode(), OperatingMode::Ready);
+}
+
+#[test]
+fn is_interrupt_asserted_reads_the_interrupt_pin() {
+    let mut sensor = sensor_with_pins(ReadyMode);
+    assert_eq!(sensor.is_interrupt_asserted(), Ok(false));
+    sensor.interrupt_pin.is_high = true;
+    assert_eq!(sensor.is_interrupt_asserted(), Ok(true));
+}